@@ -0,0 +1,85 @@
+// Radix-2 decimation in time fft of several channels of the same length, stored
+// one after another. Every stage walks the twiddle factors once and applies each
+// of them to all channels, so the table lookups are shared between the channels.
+// Takes every channel in bit reversed order.
+macro_rules! batch_impl {
+    (
+        float; $t:ty;
+        ($data:ident, $channels:ident);
+        $($signature:tt)*
+    ) => {
+        batch_impl!(
+            start: {},
+            headroom: {},
+            ($data: &mut [($t, $t)], $channels: usize);
+            $data; $channels;
+            $($signature)*
+        );
+    };
+
+    (
+        int; $t:ty; $init:literal: $exp_type:ty;
+        ($data:ident, $channels:ident, $exponents:ident);
+        $($signature:tt)*
+    ) => {
+        batch_impl!(
+            start: {
+                $exponents.fill($init);
+            },
+            // a butterfly can grow a component 1 + sqrt(2) times, so like in fft_impl
+            // the values are kept below (sqrt(2) - 1) * 2^(BITS - 1). A single exponent
+            // is shared either by all the channels or by the values of each channel.
+            headroom: {
+                if $exponents.len() == 1 {
+                    $exponents[0] += limit_dyn($data, 27131);
+                }
+                else {
+                    let len = $data.len() / $channels;
+                    for (channel, exponent) in $data.chunks_mut(len).zip($exponents.iter_mut()) {
+                        *exponent += limit_dyn(channel, 27131);
+                    }
+                }
+            },
+            ($data: &mut [($t, $t)], $channels: usize, $exponents: &mut [$exp_type]);
+            $data; $channels;
+            $($signature)*
+        );
+    };
+
+    (
+        start: $start:block,
+        headroom: $headroom:block,
+        ($($arg:ident: $arg_type:ty),*);
+        $data:ident; $channels:ident;
+        $($signature:tt)*
+    ) => {
+
+    $($signature)* ($($arg: $arg_type),*) {
+        let len = $data.len() / $channels;
+        $start
+        let mut step_log2 = 0;
+        while 1 << step_log2 < len {
+            let step = 1 << step_log2;
+            let jump = step << 1;
+            $headroom
+            for group in 0..step {
+                // the first twiddle factor is 1, skip the multiplication
+                let w = if group == 0 { twiddle(0) } else { twiddle((group as crate::Angle) << (crate::Angle::BITS - step_log2)) };
+
+                // the channel length is a multiple of jump, so this reaches every channel
+                let mut x = group;
+                while x < $data.len() {
+                    let y = x + step;
+                    let product = if group == 0 { $data[y] } else { mul($data[y], w) };
+                    let a = $data[x];
+                    $data[x] = (a.0 + product.0, a.1 + product.1);
+                    $data[y] = (a.0 - product.0, a.1 - product.1);
+                    x += jump;
+                }
+            }
+            step_log2 += 1;
+        }
+    }
+
+    };
+}
@@ -0,0 +1,247 @@
+// Streaming fft convolution of real samples with a fixed kernel. Frames of
+// F = 2 * spectrum.len() real samples are transformed two at a time with
+// rfft_pairs_twice, multiplied by the half spectrum of the kernel and brought
+// back with irfft_pairs_twice. Every call processes two blocks of
+// B = F - tail.len() (or F - history.len()) samples in place, and kernels of up
+// to F - B + 1 taps are supported.
+macro_rules! convolution_impl {
+    (float; $t:ty;) => {
+        // Multiplies both half spectra stored in `data` by the kernel spectrum
+        fn multiply_spectra(data: &mut [($t, $t)], spectrum: &[($t, $t)]) {
+            for half in data.chunks_mut(spectrum.len()) {
+                // (X[0], X[F / 2]) are both real
+                half[0] = (half[0].0 * spectrum[0].0, half[0].1 * spectrum[0].1);
+                for (x, h) in half[1..].iter_mut().zip(spectrum[1..].iter()) {
+                    *x = mul_samples(*x, *h);
+                }
+            }
+        }
+
+        convolution_impl!($t;);
+    };
+
+    (int; $t:ty; $wide:ty; $ret:ident = $ret_init:literal: $ret_type:ty) => {
+        // Multiplies both half spectra stored in `data` by the kernel spectrum. The
+        // products are formed in $wide and all shifted by the same amount, chosen
+        // so that the largest one just fits.
+        fn multiply_spectra(data: &mut [($t, $t)], spectrum: &[($t, $t)], $ret: $ret_type, spectrum_exp: $ret_type) -> $ret_type {
+            let $ret = $ret + normalize_dyn(data, 1) + spectrum_exp;
+            let product = |half: &[($t, $t)], i: usize| -> ($wide, $wide) {
+                let (x, h) = (half[i], spectrum[i]);
+                if i == 0 {
+                    // (X[0], X[F / 2]) are both real
+                    (x.0 as $wide * h.0 as $wide, x.1 as $wide * h.1 as $wide)
+                }
+                else {
+                    (
+                        x.0 as $wide * h.0 as $wide - x.1 as $wide * h.1 as $wide,
+                        x.0 as $wide * h.1 as $wide + x.1 as $wide * h.0 as $wide,
+                    )
+                }
+            };
+
+            let mut bits: $wide = 0;
+            for half in data.chunks(spectrum.len()) {
+                for i in 0..half.len() {
+                    let p = product(half, i);
+                    bits |= (p.0 ^ (p.0 >> (<$wide>::BITS - 1))) | (p.1 ^ (p.1 >> (<$wide>::BITS - 1)));
+                }
+            }
+            let shift = (<$wide>::BITS - bits.leading_zeros()).saturating_sub(<$t>::BITS - 1);
+            for half in data.chunks_mut(spectrum.len()) {
+                for i in 0..half.len() {
+                    let p = product(half, i);
+                    half[i] = ((p.0 >> shift) as $t, (p.1 >> shift) as $t);
+                }
+            }
+            $ret + shift as $ret_type
+        }
+
+        convolution_impl!($t; $ret = $ret_init: $ret_type);
+    };
+
+    ($t:ty; $($ret:ident = $ret_init:literal: $ret_type:ty)?) => {
+
+    $(
+    // number of redundant sign bits every value has
+    fn redundant_bits(data: impl Iterator<Item = $t>) -> u32 {
+        let mut bits: $t = 0;
+        for x in data {
+            bits |= x ^ (x >> (<$t>::BITS - 1));
+        }
+        bits.leading_zeros() - 1
+    }
+
+    // shifts right by `shift`, or left by -shift
+    fn rescale<'a>(data: impl Iterator<Item = &'a mut $t>, shift: $ret_type) {
+        for x in data {
+            if shift >= 0 {
+                *x >>= (shift as u32).min(<$t>::BITS - 1);
+            }
+            else {
+                *x <<= -shift as u32;
+            }
+        }
+    }
+    )?
+
+    // Half spectrum of the kernel zero-padded to F = 2 * spectrum.len() samples,
+    // for OverlapAdd and OverlapSave
+    pub fn convolution_kernel_dyn(kernel: &[$t], spectrum: &mut [($t, $t)]) $(-> $ret_type)? {
+        debug_assert!(kernel.len() <= 2 * spectrum.len());
+        spectrum.fill((0 as $t, 0 as $t));
+        for (i, &x) in kernel.iter().enumerate() {
+            *crate::dct::real_mut(spectrum, i) = x;
+        }
+        $(let $ret: $ret_type =)? rfft_dyn(spectrum);
+        // the products with the signal spectrum are formed in $wide, which
+        // needs a redundant sign bit in both factors
+        $(let $ret: $ret_type = $ret + normalize_dyn(spectrum, 1);)?
+        $($ret)?
+    }
+
+    pub struct OverlapAdd<'a> {
+        spectrum: &'a [($t, $t)],
+        $(spectrum_exp: $ret_type,)?
+        buffer: &'a mut [($t, $t)],
+        // the parts of the previous frames overlapping the next blocks
+        tail: &'a mut [$t],
+        $(tail_exp: $ret_type,)?
+    }
+
+    impl<'a> OverlapAdd<'a> {
+        // `spectrum` comes from convolution_kernel_dyn, `buffer` has to hold F values
+        // and the length of `tail` sets the block size.
+        pub fn new(
+            spectrum: &'a [($t, $t)],
+            $($ret: $ret_type,)?
+            buffer: &'a mut [($t, $t)],
+            tail: &'a mut [$t],
+        ) -> Self {
+            debug_assert!(spectrum.len().is_power_of_two() && spectrum.len() >= 2);
+            debug_assert!(buffer.len() == 2 * spectrum.len());
+            debug_assert!(tail.len() < 2 * spectrum.len());
+            tail.fill(0 as $t);
+            OverlapAdd {
+                spectrum,
+                $(spectrum_exp: $ret,)?
+                buffer,
+                tail,
+                $(tail_exp: $ret_init,)?
+            }
+        }
+
+        // Filters the 2B samples of `data` in place. The integer variants return
+        // the exponent of the output, which changes from call to call.
+        pub fn process(&mut self, data: &mut [$t]) $(-> $ret_type)? {
+            let len = 2 * self.spectrum.len();
+            let block = len - self.tail.len();
+            debug_assert!(data.len() == 2 * block);
+
+            // two zero-padded frames, one after another
+            self.buffer.fill((0 as $t, 0 as $t));
+            for (i, &x) in data.iter().enumerate() {
+                *crate::dct::real_mut(self.buffer, i / block * len + i % block) = x;
+            }
+            // full scale input can overflow the first stages of the fft
+            $(let $ret: $ret_type = $ret_init + normalize_dyn(self.buffer, 1);)?
+            $(let $ret: $ret_type = $ret - $ret_init +)? rfft_pairs_twice_dyn(self.buffer);
+            $(let $ret: $ret_type =)? multiply_spectra(self.buffer, self.spectrum $(, $ret, self.spectrum_exp)?);
+            $(let $ret: $ret_type =)? irfft_pairs_twice_dyn(self.buffer $(, $ret)?);
+
+            $(
+            // the tail and both frames can overlap, so everything is brought to
+            // a common exponent with two redundant sign bits
+            let $ret: $ret_type = {
+                let buffer_bits = redundant_bits(self.buffer.iter().flat_map(|x| [x.0, x.1]));
+                let tail_bits = redundant_bits(self.tail.iter().copied());
+                let exp = ($ret + 2 - buffer_bits as $ret_type).max(self.tail_exp + 2 - tail_bits as $ret_type);
+                rescale(self.buffer.iter_mut().flat_map(|x| [&mut x.0, &mut x.1]), exp - $ret);
+                rescale(self.tail.iter_mut(), exp - self.tail_exp);
+                self.tail_exp = exp;
+                exp
+            };
+            )?
+
+            let overlap = self.tail.len();
+            for frame in 0..2 {
+                for i in 0..block {
+                    let carry = if i < overlap { self.tail[i] } else { 0 as $t };
+                    data[frame * block + i] = *crate::dct::real_mut(self.buffer, frame * len + i) + carry;
+                }
+                for i in 0..overlap {
+                    let carry = if block + i < overlap { self.tail[block + i] } else { 0 as $t };
+                    self.tail[i] = *crate::dct::real_mut(self.buffer, frame * len + block + i) + carry;
+                }
+            }
+            $($ret)?
+        }
+    }
+
+    pub struct OverlapSave<'a> {
+        spectrum: &'a [($t, $t)],
+        $(spectrum_exp: $ret_type,)?
+        buffer: &'a mut [($t, $t)],
+        // the last input samples, which the next frame starts with
+        history: &'a mut [$t],
+    }
+
+    impl<'a> OverlapSave<'a> {
+        // `spectrum` comes from convolution_kernel_dyn, `buffer` has to hold F values
+        // and the length of `history` sets the block size.
+        pub fn new(
+            spectrum: &'a [($t, $t)],
+            $($ret: $ret_type,)?
+            buffer: &'a mut [($t, $t)],
+            history: &'a mut [$t],
+        ) -> Self {
+            debug_assert!(spectrum.len().is_power_of_two() && spectrum.len() >= 2);
+            debug_assert!(buffer.len() == 2 * spectrum.len());
+            debug_assert!(history.len() < 2 * spectrum.len());
+            history.fill(0 as $t);
+            OverlapSave {
+                spectrum,
+                $(spectrum_exp: $ret,)?
+                buffer,
+                history,
+            }
+        }
+
+        // Filters the 2B samples of `data` in place. The integer variants return
+        // the exponent of the output, which changes from call to call.
+        pub fn process(&mut self, data: &mut [$t]) $(-> $ret_type)? {
+            let len = 2 * self.spectrum.len();
+            let overlap = self.history.len();
+            let block = len - overlap;
+            debug_assert!(data.len() == 2 * block);
+
+            // the input stream is the history followed by the data, the frames
+            // start at 0 and at B
+            let stream = |history: &[$t], i: usize| if i < overlap { history[i] } else { data[i - overlap] };
+            for frame in 0..2 {
+                for i in 0..len {
+                    *crate::dct::real_mut(self.buffer, frame * len + i) = stream(self.history, frame * block + i);
+                }
+            }
+            for i in 0..overlap {
+                self.history[i] = stream(self.history, 2 * block + i);
+            }
+
+            // full scale input can overflow the first stages of the fft
+            $(let $ret: $ret_type = $ret_init + normalize_dyn(self.buffer, 1);)?
+            $(let $ret: $ret_type = $ret - $ret_init +)? rfft_pairs_twice_dyn(self.buffer);
+            $(let $ret: $ret_type =)? multiply_spectra(self.buffer, self.spectrum $(, $ret, self.spectrum_exp)?);
+            $(let $ret: $ret_type =)? irfft_pairs_twice_dyn(self.buffer $(, $ret)?);
+
+            // the first F - B values of each frame are wrapped around, the rest is valid
+            for frame in 0..2 {
+                for i in 0..block {
+                    data[frame * block + i] = *crate::dct::real_mut(self.buffer, frame * len + overlap + i);
+                }
+            }
+            $($ret)?
+        }
+    }
+
+    };
+}
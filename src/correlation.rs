@@ -0,0 +1,253 @@
+// Cross-correlation of two real signals through their spectra, computed with
+// rfft_pairs_twice. Both signals are zero-padded to N = data.len() samples and
+// r[m] = sum a[n] * b[n + m] is computed circularly, which equals the linear
+// cross-correlation when a.len() + b.len() - 1 <= N. Positive lags mean that b
+// is delayed relative to a, and the lag -m is stored at N - m.
+// The autocorrelation follows from the Wiener-Khinchin theorem, as the inverse
+// transform of the power spectrum |X[k]|^2.
+macro_rules! correlation_impl {
+    (float; $t:ty;) => {
+        // x / |x|, or 0 for x = 0. The estimate of 1 / sqrt(s) halves the exponent
+        // of s and is refined with Newton's method.
+        fn unit(x: ($t, $t)) -> ($t, $t) {
+            let s = x.0 * x.0 + x.1 * x.1;
+            if s == 0. {
+                return (0., 0.);
+            }
+            let one = (1. as $t).to_bits();
+            let mut y = <$t>::from_bits(one + one / 2 - s.to_bits() / 2);
+            for _ in 0..5 {
+                y *= 1.5 - 0.5 * s * y * y;
+            }
+            (x.0 * y, x.1 * y)
+        }
+
+        // offset of the vertex of the parabola through three values
+        // around a maximum, in [-0.5, 0.5]
+        fn peak_offset(before: $t, peak: $t, after: $t) -> $t {
+            let den = 2. * peak - before - after;
+            if den == 0. { 0. } else { 0.5 * (after - before) / den }
+        }
+
+        // |X[k]|^2 of a half spectrum in the layout of rfft
+        fn power_spectrum(data: &mut [($t, $t)]) {
+            data[0] = (data[0].0 * data[0].0, data[0].1 * data[0].1);
+            for x in data[1..].iter_mut() {
+                *x = (x.0 * x.0 + x.1 * x.1, 0.);
+            }
+        }
+
+        // divides the values at the lags 0..len by the number of products
+        // each of them sums
+        fn estimate(data: &mut [($t, $t)], estimator: crate::Estimator) {
+            let len = 2 * data.len();
+            for m in 0..len {
+                let count = match estimator {
+                    crate::Estimator::Biased => len,
+                    crate::Estimator::Unbiased => len - m,
+                };
+                *crate::dct::real_mut(data, m) /= count as $t;
+            }
+        }
+
+        correlation_impl!($t;);
+    };
+
+    (int; $t:ty; $wide:ty; $ret:ident = $ret_init:literal: $ret_type:ty) => {
+        // x / |x| with the exponent $ret_init + 1, so the components stay below
+        // 2^(BITS - 2). Values of 0 and -1 have no direction and give 0.
+        fn unit(x: ($t, $t)) -> ($t, $t) {
+            let (re, im) = (x.0 as $wide, x.1 as $wide);
+            let bits = (re ^ (re >> (<$wide>::BITS - 1))) | (im ^ (im >> (<$wide>::BITS - 1)));
+            if bits == 0 {
+                return (0, 0);
+            }
+            // BITS - 2 magnitude bits, so that the sum of squares fits
+            let top = <$wide>::BITS - bits.leading_zeros();
+            let (re, im) = if top > <$t>::BITS - 2 {
+                (re >> (top + 2 - <$t>::BITS), im >> (top + 2 - <$t>::BITS))
+            }
+            else {
+                (re << (<$t>::BITS - 2 - top), im << (<$t>::BITS - 2 - top))
+            };
+            let len = (re * re + im * im).isqrt();
+            (((re << (<$t>::BITS - 2)) / len) as $t, ((im << (<$t>::BITS - 2)) / len) as $t)
+        }
+
+        // offset of the vertex of the parabola through three values around
+        // a maximum, in [-0.5, 0.5] as Q(BITS - 1)
+        fn peak_offset(before: $t, peak: $t, after: $t) -> $t {
+            let den = 2 * peak as $wide - before as $wide - after as $wide;
+            if den == 0 { 0 } else { (((after as $wide - before as $wide) << (<$t>::BITS - 2)) / den) as $t }
+        }
+
+        // x * 2^exp as Q(BITS - 1), saturated
+        fn to_fraction(x: $t, exp: $ret_type) -> $t {
+            let shift = exp - $ret_init;
+            if shift <= 0 {
+                x >> (-shift as u32).min(<$t>::BITS - 1)
+            }
+            else if (shift as u32) < (x ^ (x >> (<$t>::BITS - 1))).leading_zeros() {
+                x << shift
+            }
+            else if x < 0 { <$t>::MIN } else { <$t>::MAX }
+        }
+
+        // |X[k]|^2 of a half spectrum in the layout of rfft. The squares are formed
+        // in $wide and all shifted by the same amount, chosen so that the largest
+        // one just fits.
+        fn power_spectrum(data: &mut [($t, $t)], $ret: $ret_type) -> $ret_type {
+            let $ret = $ret + normalize_dyn(data, 1);
+            let square = |x: $t| x as $wide * x as $wide;
+            let mut bits: $wide = square(data[0].0) | square(data[0].1);
+            for x in data[1..].iter() {
+                bits |= square(x.0) + square(x.1);
+            }
+            let shift = (<$wide>::BITS - bits.leading_zeros()).saturating_sub(<$t>::BITS - 1);
+            data[0] = ((square(data[0].0) >> shift) as $t, (square(data[0].1) >> shift) as $t);
+            for x in data[1..].iter_mut() {
+                *x = (((square(x.0) + square(x.1)) >> shift) as $t, 0);
+            }
+            2 * $ret + shift as $ret_type
+        }
+
+        // divides the values at the lags 0..len by the number of products each
+        // of them sums. For the biased estimate this is a power of two which only
+        // changes the exponent, the unbiased one is formed in $wide like the spectrum.
+        fn estimate(data: &mut [($t, $t)], estimator: crate::Estimator, $ret: $ret_type) -> $ret_type {
+            let len = 2 * data.len();
+            if estimator == crate::Estimator::Biased {
+                return $ret - len.trailing_zeros() as $ret_type;
+            }
+            let quotient = |data: &mut [($t, $t)], m: usize| {
+                ((*crate::dct::real_mut(data, m) as $wide) << (<$t>::BITS - 1)) / (len - m) as $wide
+            };
+
+            let mut bits: $wide = 0;
+            for m in 0..len {
+                let q = quotient(data, m);
+                bits |= q ^ (q >> (<$wide>::BITS - 1));
+            }
+            let shift = (<$wide>::BITS - bits.leading_zeros()).saturating_sub(<$t>::BITS - 1);
+            for m in 0..len {
+                *crate::dct::real_mut(data, m) = (quotient(data, m) >> shift) as $t;
+            }
+            $ret - (<$t>::BITS - 1) as $ret_type + shift as $ret_type
+        }
+
+        correlation_impl!($t; $ret = $ret_init: $ret_type);
+    };
+
+    ($t:ty; $($ret:ident = $ret_init:literal: $ret_type:ty)?) => {
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Delay {
+        // lag of the largest value of the correlation
+        pub lag: isize,
+        // sub-sample correction in [-0.5, 0.5] to add to the lag, Q(BITS - 1) for
+        // the integer types
+        pub fraction: $t,
+        // height of the peak, 1 when b is an exact delayed copy of a
+        pub confidence: $t,
+    }
+
+    // conj(A[k]) * B[k] in the first half of `data`, laid out like the output of rfft
+    fn cross_spectrum_dyn(a: &[$t], b: &[$t], data: &mut [($t, $t)]) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two() && data.len() >= 4);
+        debug_assert!(a.len() <= data.len() && b.len() <= data.len());
+        let len = data.len();
+        data.fill((0 as $t, 0 as $t));
+        for (i, &x) in a.iter().enumerate() {
+            *crate::dct::real_mut(data, i) = x;
+        }
+        for (i, &x) in b.iter().enumerate() {
+            *crate::dct::real_mut(data, len + i) = x;
+        }
+        $(let $ret: $ret_type =)? rfft_pairs_twice_dyn(data);
+
+        // the products are formed by multiply_spectra of the fft convolution
+        let (spectrum_a, spectrum_b) = data.split_at_mut(len / 2);
+        $(let spectrum_exp: $ret_type = $ret + normalize_dyn(spectrum_b, 1);)?
+        $(let $ret: $ret_type = $ret + normalize_dyn(spectrum_a, 1);)?
+        for x in spectrum_a[1..].iter_mut() {
+            x.1 = -x.1;
+        }
+        multiply_spectra(spectrum_a, spectrum_b $(, $ret, spectrum_exp)?)
+    }
+
+    // Circular cross-correlation of a and b, the N real values are stored in
+    // the first half of `data` in the layout of rfft. The second half is used
+    // as scratch space.
+    pub fn cross_correlation_dyn(a: &[$t], b: &[$t], data: &mut [($t, $t)]) $(-> $ret_type)? {
+        $(let $ret: $ret_type =)? cross_spectrum_dyn(a, b, data);
+        let half = data.len() / 2;
+        irfft_dyn(&mut data[..half] $(, $ret)?)
+    }
+
+    // Lag of the largest value of a correlation within -max_lag..=max_lag, and
+    // the sub-sample correction from a parabola through it and its neighbours.
+    // `data` holds the N = 2 * data.len() real values, as produced by
+    // cross_correlation_dyn.
+    pub fn correlation_peak_dyn(data: &[($t, $t)], max_lag: usize) -> (isize, $t) {
+        let len = 2 * data.len();
+        debug_assert!(max_lag < len / 2);
+        let value = |lag: isize| {
+            let i = lag.rem_euclid(len as isize) as usize;
+            if i & 1 == 0 { data[i / 2].0 } else { data[i / 2].1 }
+        };
+
+        let mut lag = 0;
+        for m in 1..=max_lag as isize {
+            for m in [m, -m] {
+                if value(m) > value(lag) {
+                    lag = m;
+                }
+            }
+        }
+        (lag, peak_offset(value(lag - 1), value(lag), value(lag + 1)))
+    }
+
+    // Delay of b relative to a by the generalized cross-correlation with phase
+    // transform (GCC-PHAT). Every bin of the cross spectrum is scaled to unit
+    // magnitude, which keeps only the phase differences and gives a sharp peak
+    // even for narrowband or reverberant signals. Afterwards the first half of
+    // `data` holds the weighted correlation, as for cross_correlation_dyn.
+    pub fn gcc_phat_dyn(a: &[$t], b: &[$t], data: &mut [($t, $t)], max_lag: usize) -> Delay {
+        $(let _: $ret_type =)? cross_spectrum_dyn(a, b, data);
+        let half = data.len() / 2;
+        let spectrum = &mut data[..half];
+        // (X[0], X[N / 2]) are both real
+        spectrum[0] = (unit((spectrum[0].0, 0 as $t)).0, unit((spectrum[0].1, 0 as $t)).0);
+        for x in spectrum[1..].iter_mut() {
+            *x = unit(*x);
+        }
+        $(let $ret: $ret_type =)? irfft_dyn(spectrum $(, $ret_init + 1)?);
+
+        let (lag, fraction) = correlation_peak_dyn(spectrum, max_lag);
+        let i = lag.rem_euclid(2 * half as isize) as usize;
+        let confidence = *crate::dct::real_mut(spectrum, i);
+        $(let confidence = to_fraction(confidence, $ret);)?
+        Delay { lag, fraction, confidence }
+    }
+
+    // Autocorrelation of the N = data.len() real samples in the first half of
+    // `data`, r[m] = sum x[n] * x[n + m], computed as the inverse transform of
+    // |X[k]|^2. The samples are zero-padded to 2N, so the lags 0..N are not
+    // affected by the circular wrap around. Afterwards the first half holds
+    // the estimate at these lags, the second half is used as scratch space.
+    pub fn autocorrelate<const N: usize>(data: &mut [($t, $t); N], estimator: crate::Estimator) $(-> $ret_type)? {
+        autocorrelate_dyn(data, estimator)
+    }
+
+    pub fn autocorrelate_dyn(data: &mut [($t, $t)], estimator: crate::Estimator) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two() && data.len() >= 4);
+        let half = data.len() / 2;
+        data[half..].fill((0 as $t, 0 as $t));
+        $(let $ret: $ret_type =)? rfft_dyn(data);
+        $(let $ret: $ret_type =)? power_spectrum(data $(, $ret)?);
+        $(let $ret: $ret_type =)? irfft_dyn(data $(, $ret)?);
+        estimate(&mut data[..half], estimator $(, $ret)?)
+    }
+
+    };
+}
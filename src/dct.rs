@@ -0,0 +1,224 @@
+// Discrete cosine transforms computed with the real fft (Makhoul 1980).
+// The real samples use the same layout as rfft, x[2k] and x[2k + 1] are
+// stored in the k-th pair, and the coefficients are returned the same way.
+
+pub(crate) fn real_mut<T>(arr: &mut [(T, T)], i: usize) -> &mut T {
+    let pair = &mut arr[i / 2];
+    if i & 1 == 0 { &mut pair.0 } else { &mut pair.1 }
+}
+
+// Reverses the order of the real values start..end, both halves of a pair count
+pub(crate) fn reverse_reals_dyn<T: Copy>(arr: &mut [(T, T)], start: usize, end: usize) {
+    let (mut i, mut j) = (start, end);
+    while i + 1 < j {
+        j -= 1;
+        let (a, b) = (*real_mut(arr, i), *real_mut(arr, j));
+        *real_mut(arr, i) = b;
+        *real_mut(arr, j) = a;
+        i += 1;
+    }
+}
+
+// MDCT input (a, b, c, d) to the DCT-IV input (-c_r - d, a - b_r), written to the first
+// half. `_r` denotes reversal. Indices n and n' = N / 2 - 1 - n read and write the
+// same positions, so they are handled together.
+pub(crate) fn mdct_fold_dyn<T>(arr: &mut [(T, T)])
+where
+    T: Copy + core::ops::Sub<Output = T> + core::ops::Neg<Output = T>
+{
+    let half = arr.len() / 2;
+    for n in 0..half / 2 {
+        let mut u = [*real_mut(arr, 0); 4];
+        for (i, n) in [n, half - 1 - n].into_iter().enumerate() {
+            u[2 * i] = -*real_mut(arr, 3 * half - 1 - n) - *real_mut(arr, 3 * half + n);
+            u[2 * i + 1] = *real_mut(arr, n) - *real_mut(arr, arr.len() - 1 - n);
+        }
+        for (i, n) in [n, half - 1 - n].into_iter().enumerate() {
+            *real_mut(arr, n) = u[2 * i];
+            *real_mut(arr, half + n) = u[2 * i + 1];
+        }
+    }
+}
+
+// DCT-IV output (w1, w2) in the first half to the IMDCT output (w2, -w2_r, -w1_r, -w1)
+pub(crate) fn imdct_unfold_dyn<T>(arr: &mut [(T, T)])
+where
+    T: Copy + core::ops::Neg<Output = T>
+{
+    let (half, len) = (arr.len() / 2, 2 * arr.len());
+    for j in 0..half {
+        *real_mut(arr, len / 2 + j) = -*real_mut(arr, half - 1 - j);
+        *real_mut(arr, 3 * half + j) = -*real_mut(arr, j);
+    }
+    for n in 0..half / 2 {
+        let (a, b) = (*real_mut(arr, half + n), *real_mut(arr, len / 2 - 1 - n));
+        *real_mut(arr, n) = a;
+        *real_mut(arr, len / 2 - 1 - n) = -a;
+        *real_mut(arr, half - 1 - n) = b;
+        *real_mut(arr, half + n) = -b;
+    }
+}
+
+macro_rules! dct_impl {
+    (float; $t:ty;) => {
+        dct_impl!(
+            $t;;
+            // scale by 2/N, where N is the number of coefficients
+            imdct_scale: |data| {
+                let scale = 2. / data.len() as $t;
+                for x in data.iter_mut() {
+                    *x = mul_real(*x, scale);
+                }
+            }
+        );
+    };
+
+    (int; $t:ty; $ret:ident = $ret_init:literal: $ret_type:ty) => {
+        dct_impl!(
+            $t; $ret = $ret_init: $ret_type;
+            // the 2/N factor only affects the exponent
+            imdct_scale: |data| {
+                $ret + 1 - data.len().trailing_zeros() as $ret_type
+            }
+        );
+    };
+
+    ($t:ty; $($ret:ident = $ret_init:literal: $ret_type:ty)?; imdct_scale: |$data:ident| $imdct_scale:block) => {
+
+    // X[k] = sum x[n] * cos(pi * (2n + 1) * k / 2N), over the N = 2 * data.len() real samples
+    pub fn dct2<const N: usize>(data: &mut [($t, $t); N]) $(-> $ret_type)? {
+        dct2_dyn(data)
+    }
+
+    pub fn dct2_dyn(data: &mut [($t, $t)]) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two() && data.len() >= 4);
+        let len = 2 * data.len();
+
+        // v = (x[0], x[2], ..., x[N - 2], x[N - 1], ..., x[3], x[1])
+        super::deinterleave_dyn(data);
+        crate::dct::reverse_reals_dyn(data, len / 2, len);
+        $(let $ret: $ret_type =)? rfft_dyn(data);
+        // a rotated value can have components sqrt(2) times larger
+        $(let $ret: $ret_type = $ret + normalize_dyn(data, 1);)?
+
+        // X[k] - i * X[N - k] = V[k] * e^(-pi i * k / 2N), stored as the k-th pair
+        let shift = crate::Angle::BITS - 1 - len.trailing_zeros();
+        data[0].1 = mul((data[0].1, 0 as $t), twiddle((data.len() as crate::Angle) << shift)).0;
+        for k in 1..data.len() {
+            let z = mul(data[k], twiddle((k as crate::Angle) << shift));
+            data[k] = (z.0, -z.1);
+        }
+
+        // (X[0], X[N / 2], X[1], X[N - 1], ...) to natural order
+        super::deinterleave_dyn(data);
+        crate::dct::reverse_reals_dyn(data, len / 2 + 1, len);
+        $($ret)?
+    }
+
+    // Inverse of dct2, which is the DCT-III scaled by 2 / N
+    pub fn dct3<const N: usize>(data: &mut [($t, $t); N] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        dct3_dyn(data $(, $ret)?)
+    }
+
+    pub fn dct3_dyn(data: &mut [($t, $t)] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two() && data.len() >= 4);
+        let len = 2 * data.len();
+
+        crate::dct::reverse_reals_dyn(data, len / 2 + 1, len);
+        super::interleave_dyn(data);
+        // X[N / 2] gets doubled below, the rotated values can grow sqrt(2) times
+        $(let $ret: $ret_type = $ret + normalize_dyn(data, 1);)?
+
+        // V[k] = (X[k] - i * X[N - k]) * e^(pi i * k / 2N), V[N / 2] = sqrt(2) * X[N / 2]
+        let shift = crate::Angle::BITS - 1 - len.trailing_zeros();
+        data[0].1 = mul((data[0].1 + data[0].1, 0 as $t), twiddle((data.len() as crate::Angle) << shift)).0;
+        for k in 1..data.len() {
+            let w = twiddle((k as crate::Angle) << shift);
+            data[k] = mul((data[k].0, -data[k].1), (w.0, -w.1));
+        }
+        $(let $ret: $ret_type =)? irfft_dyn(data $(, $ret)?);
+
+        crate::dct::reverse_reals_dyn(data, len / 2, len);
+        super::interleave_dyn(data);
+        $($ret)?
+    }
+
+    // X[k] = sum x[n] * cos(pi * (n + 1/2) * (k + 1/2) / N), over the N = 2 * data.len()
+    // real samples. Applying it twice gives the input scaled by N / 2.
+    pub fn dct4<const N: usize>(data: &mut [($t, $t); N]) $(-> $ret_type)? {
+        dct4_dyn(data)
+    }
+
+    pub fn dct4_dyn(data: &mut [($t, $t)]) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two() && data.len() >= 2);
+        let len = 2 * data.len();
+
+        // c[n] = x[2n] + i * x[N - 1 - 2n]
+        for n in 0..data.len() / 2 {
+            let m = data.len() - 1 - n;
+            (data[n].1, data[m].1) = (data[m].1, data[n].1);
+        }
+        // a rotated value can have components sqrt(2) times larger
+        $(let $ret: $ret_type = $ret_init + normalize_dyn(data, 1);)?
+        let shift = crate::Angle::BITS - len.trailing_zeros();
+        for n in 1..data.len() {
+            data[n] = mul(data[n], twiddle((n as crate::Angle) << shift));
+        }
+
+        $(let $ret: $ret_type = $ret - $ret_init +)? fft_pairs_dyn(data);
+
+        // X[2k] - i * X[N - 1 - 2k] = C[k] * e^(-pi i * (4k + 1) / 4N)
+        $(let $ret: $ret_type = $ret + normalize_dyn(data, 1);)?
+        for k in 0..data.len() {
+            let z = mul(data[k], twiddle(((4 * k + 1) as crate::Angle) << (shift - 2)));
+            data[k] = (z.0, -z.1);
+        }
+        for k in 0..data.len() / 2 {
+            let m = data.len() - 1 - k;
+            (data[k].1, data[m].1) = (data[m].1, data[k].1);
+        }
+        $($ret)?
+    }
+
+    // MDCT of the 2N = 2 * data.len() real samples, the N coefficients
+    // X[k] = sum x[n] * cos(pi * (n + 1/2 + N/2) * (k + 1/2) / N) are stored in
+    // the first half of `data`. The samples should be windowed beforehand.
+    pub fn mdct<const N: usize>(data: &mut [($t, $t); N]) $(-> $ret_type)? {
+        mdct_dyn(data)
+    }
+
+    pub fn mdct_dyn(data: &mut [($t, $t)]) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two() && data.len() >= 4);
+        // folding adds two samples together
+        $(let $ret: $ret_type = $ret_init + normalize_dyn(data, 1);)?
+        crate::dct::mdct_fold_dyn(data);
+        let half = data.len() / 2;
+        $(let $ret: $ret_type = $ret - $ret_init +)? dct4_dyn(&mut data[..half]);
+        $($ret)?
+    }
+
+    // IMDCT of the N coefficients in the first half of `data`, scaled by 2 / N,
+    // producing 2N = 2 * data.len() real samples. With a window satisfying
+    // w[n]^2 + w[n + N]^2 = 1 applied before the mdct and again here, adding the
+    // overlapping halves of consecutive frames together gives back the input.
+    // For the integer types the frames have to be brought to a common exponent first.
+    pub fn imdct<const N: usize>(data: &mut [($t, $t); N] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        imdct_dyn(data $(, $ret)?)
+    }
+
+    pub fn imdct_dyn(data: &mut [($t, $t)] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two() && data.len() >= 4);
+        let half = data.len() / 2;
+        $(let $ret: $ret_type = $ret - $ret_init +)? dct4_dyn(&mut data[..half]);
+        // the unfolding negates values
+        $(let $ret: $ret_type = $ret + normalize_dyn(&mut data[..half], 1);)?
+        crate::dct::imdct_unfold_dyn(data);
+        $(let $ret: $ret_type =)? {
+            let $data = &mut *data;
+            $imdct_scale
+        };
+        $($ret)?
+    }
+
+    };
+}
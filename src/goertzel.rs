@@ -0,0 +1,123 @@
+// Goertzel algorithm, evaluates single dft bins of real samples with the second
+// order recursion s[n] = x[n] + 2 cos(w) * s[n - 1] - s[n - 2], followed by
+// y = s[N - 1] - e^(-i w) * s[N - 2]. y only differs from X(w) in phase, so the
+// power |X(w)|^2 is returned.
+// Close to 0 and pi the frequency is very sensitive to the error of 2 cos(w), so
+// the coefficients are computed from the twiddle factor of w / 2, where
+// 2 cos(w) = 2 - 4 sin^2(w / 2) = 4 cos^2(w / 2) - 2 and sin(w) = 2 sin(w / 2) cos(w / 2).
+macro_rules! goertzel_impl {
+    (float; $t:ty;) => {
+        fn goertzel_bank(data: &[$t], half_twiddles: impl Iterator<Item = Twiddle>, out: &mut [$t]) {
+            for (h, power) in half_twiddles.zip(out.iter_mut()) {
+                let (cos, sin) = (h.0, -h.1);
+                let c = if sin < cos { 2. - 4. * sin * sin } else { 4. * cos * cos - 2. };
+                let (mut s1, mut s2) = (0 as $t, 0 as $t);
+                for &x in data {
+                    (s1, s2) = (x + c * s1 - s2, s1);
+                }
+                let y = (s1 - 0.5 * c * s2, 2. * sin * cos * s2);
+                *power = y.0 * y.0 + y.1 * y.1;
+            }
+        }
+
+        goertzel_impl!($t; $t;);
+    };
+
+    (int; $t:ty; $wide:ty; $ret:ident = $ret_init:literal: $ret_type:ty) => {
+        // s * q / 2^(2 MUL_SHIFT - 2), rounded to nearest, for a product q of two
+        // twiddle components. 2 cos(w) = 2 - 4 sin^2(w / 2) is carried as sin^2(w / 2)
+        // with 2 MUL_SHIFT fractional bits, with MUL_SHIFT bits the coefficient of
+        // a low frequency would round to 2 and the resonator would end up at DC.
+        fn goertzel_mul(s: $wide, q: $wide) -> $wide {
+            let shift = 2 * MUL_SHIFT - 2;
+            ((s as i128 * q as i128 + (1 << (shift - 1))) >> shift) as $wide
+        }
+
+        fn goertzel_bank(data: &[$t], half_twiddles: impl Iterator<Item = Twiddle>, out: &mut [$t]) -> $ret_type {
+            // near 0 and pi the state grows with N^2 rather than N, so it is kept
+            // in $wide, below an eighth of its range
+            let limit = (1 as $wide) << (<$wide>::BITS - 3);
+            let mut $ret: $ret_type = 2 * $ret_init;
+            for (i, h) in half_twiddles.take(out.len()).enumerate() {
+                let (cos, sin) = (h.0, -h.1);
+                // c * s = 2 s - 4 sin^2(w / 2) s, or the negated 2 s - 4 cos^2(w / 2) s
+                // close to pi
+                let (q, near_pi) = if sin < cos { (sin * sin, false) } else { (cos * cos, true) };
+                let c_mul = |s: $wide| {
+                    let y = 2 * s - goertzel_mul(s, q);
+                    if near_pi { -y } else { y }
+                };
+
+                let (mut s1, mut s2, mut shift): ($wide, $wide, u32) = (0, 0, 0);
+                for &x in data {
+                    // the shifts round to nearest, truncation would add a bias
+                    // which the recursion amplifies near 0 and pi
+                    let x = if shift < <$t>::BITS { (x as $wide + ((1 << shift) >> 1)) >> shift } else { 0 };
+                    let mut s0 = x + c_mul(s1) - s2;
+                    while s0 >= limit || s0 < -limit {
+                        s0 = (s0 + 1) >> 1;
+                        s1 = (s1 + 1) >> 1;
+                        shift += 1;
+                    }
+                    (s1, s2) = (s0, s1);
+                }
+
+                // y with two redundant sign bits in $t, so that the power fits in $wide
+                // sin(w) * s = 2 sin(w / 2) cos(w / 2) s
+                let y = (s1 - ((c_mul(s2) + 1) >> 1), (goertzel_mul(s2, sin * cos) + 1) >> 1);
+                let bits = (y.0 ^ (y.0 >> (<$wide>::BITS - 1))) | (y.1 ^ (y.1 >> (<$wide>::BITS - 1)));
+                let y_shift = (<$wide>::BITS - bits.leading_zeros()).saturating_sub(<$t>::BITS - 2);
+                let y = (y.0 >> y_shift, y.1 >> y_shift);
+                let power = y.0 * y.0 + y.1 * y.1;
+
+                // keep BITS - 1 significant bits, all bins share the largest exponent
+                let excess = (<$wide>::BITS - power.leading_zeros()).saturating_sub(<$t>::BITS - 1);
+                let exp = 2 * ($ret_init + (shift + y_shift) as $ret_type) + excess as $ret_type;
+                if i == 0 || exp > $ret {
+                    if i != 0 {
+                        let diff = ((exp - $ret) as u32).min(<$t>::BITS - 1);
+                        for x in out[..i].iter_mut() {
+                            *x >>= diff;
+                        }
+                    }
+                    $ret = exp;
+                }
+                out[i] = ((power >> excess) >> (($ret - exp) as u32).min(<$t>::BITS - 1)) as $t;
+            }
+            $ret
+        }
+
+        goertzel_impl!($t; ($t, $ret_type); $ret: $ret_type);
+    };
+
+    ($t:ty; $single:ty; $($ret:ident: $ret_type:ty)?) => {
+
+    // Power at out.len() arbitrary frequencies given as Angles, so 2^32 is half the
+    // sample rate. The integer variants return one exponent shared by all the bins.
+    pub fn goertzel_bank_dyn(data: &[$t], freqs: &[crate::Angle], out: &mut [$t]) $(-> $ret_type)? {
+        debug_assert!(out.len() <= freqs.len());
+        goertzel_bank(data, freqs.iter().map(|&freq| twiddle(freq >> 1)), out)
+    }
+
+    // Power of the bins k <= N / 2 of the N = data.len() point dft
+    pub fn goertzel_bins_dyn(data: &[$t], bins: &[usize], out: &mut [$t]) $(-> $ret_type)? {
+        debug_assert!(out.len() <= bins.len());
+        debug_assert!(bins.iter().all(|&k| 2 * k <= data.len()));
+        goertzel_bank(data, bins.iter().map(|&k| twiddle_ratio(k, 2 * data.len())), out)
+    }
+
+    // Power at a single frequency, with its exponent for the integer variants
+    pub fn goertzel_dyn(data: &[$t], freq: crate::Angle) -> $single {
+        let mut power = [0 as $t];
+        $(let $ret: $ret_type =)? goertzel_bank_dyn(data, &[freq], &mut power);
+        (power[0] $(, $ret)?)
+    }
+
+    pub fn goertzel_bin_dyn(data: &[$t], k: usize) -> $single {
+        let mut power = [0 as $t];
+        $(let $ret: $ret_type =)? goertzel_bins_dyn(data, &[k], &mut power);
+        (power[0] $(, $ret)?)
+    }
+
+    };
+}
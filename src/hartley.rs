@@ -0,0 +1,118 @@
+// Radix-2 decimation in time fast Hartley transform (Bracewell 1984),
+// H[k] = sum x[n] * (cos(2 pi n k / N) + sin(2 pi n k / N)), with real values
+// in and out. Takes the input in bit reversed order. Butterflies k and
+// half - k of a block share the same twiddle factor, so they are done together.
+// The transform is its own inverse up to a factor of N.
+macro_rules! fht_impl {
+    (
+        float; $t:ty; $len:expr;
+        ($($arg:ident: $arg_type:ty),*);
+        $x:ident; $x_val:expr;
+        fn $($signature:tt)*
+    ) => {
+        fht_impl!(
+            $len,
+            headroom: |_bits| {},
+            ($($arg: $arg_type),*);
+            $x; $x_val;
+            fn $($signature)*
+        );
+    };
+
+    (
+        int; $t:ty; $wide:ty; $len:expr;
+        ($($arg:ident: $arg_type:ty),*) $(-> $ret:ident: $ret_type:ty)?;
+        $x:ident; $x_val:expr;
+        $($signature:tt)*
+    ) => {
+        fht_impl!(
+            $len,
+            headroom: |bits| {
+                let mut acc: $t = 0;
+                let mut $x = 0;
+                while $x < $len {
+                    acc |= $x_val ^ ($x_val >> (<$t>::BITS - 1));
+                    $x += 1;
+                }
+                let shift = bits.saturating_sub(acc.leading_zeros() - 1);
+                if shift != 0 {
+                    let mut $x = 0;
+                    while $x < $len {
+                        $x_val >>= shift;
+                        $x += 1;
+                    }
+                }
+                $($ret += shift as $ret_type;)?
+            },
+            ($($arg: $arg_type),*) $(-> $ret: $ret_type)?;
+            $x; $x_val;
+            $($signature)*
+        );
+    };
+
+    (
+        $len:expr,
+        headroom: |$bits:ident| $headroom:block,
+        ($($arg:ident: $arg_type:ty),*) $(-> $ret:ident: $ret_type:ty)?;
+        $x:ident; $x_val:expr;
+        $($signature:tt)*
+    ) => {
+
+    $($signature)* ($($arg: $arg_type),* $(, mut $ret: $ret_type)?) $(-> $ret_type)? {
+        let mut half = 1;
+        while half < $len {
+            // a butterfly can grow the values 1 + sqrt(2) times
+            { let $bits: u32 = 2; $headroom }
+            let size = half << 1;
+            let mut k = 0;
+            while k <= half / 2 {
+                if k == 0 || 2 * k == half {
+                    // the twiddle factors are trivial, cos + sin = 1
+                    let mut base = 0;
+                    while base < $len {
+                        let $x = base + k;
+                        let a = $x_val;
+                        let $x = base + half + k;
+                        let b = $x_val;
+                        $x_val = a - b;
+                        let $x = base + k;
+                        $x_val = a + b;
+                        base += size;
+                    }
+                }
+                else {
+                    // w = e^(-i * theta), theta = 2 pi k / size
+                    let w = twiddle((k as crate::Angle) << (crate::Angle::BITS + 1 - size.trailing_zeros()));
+
+                    let mut base = 0;
+                    while base < $len {
+                        let $x = base + half + k;
+                        let u = $x_val;
+                        let $x = base + size - k;
+                        let v = $x_val;
+                        // (u * cos + v * sin, u * sin - v * cos)
+                        let t = mul((u, v), w);
+                        let t = (t.0, -t.1);
+
+                        let $x = base + k;
+                        let a = $x_val;
+                        $x_val = a + t.0;
+                        let $x = base + half + k;
+                        $x_val = a - t.0;
+                        let $x = base + half - k;
+                        let b = $x_val;
+                        $x_val = b + t.1;
+                        let $x = base + size - k;
+                        $x_val = b - t.1;
+                        base += size;
+                    }
+                }
+                k += 1;
+            }
+            half = size;
+        }
+        $($ret)?
+    }
+
+    };
+}
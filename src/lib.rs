@@ -2,11 +2,73 @@
 #![cfg_attr(feature = "const", feature(const_mut_refs))]
 #![cfg_attr(feature = "const", feature(const_swap))]
 mod tables;
+#[macro_use]
+mod radix4;
+#[macro_use]
+mod split_radix;
+#[macro_use]
+mod mixed_radix;
+#[macro_use]
+mod batch;
+#[macro_use]
+mod dct;
+#[macro_use]
+mod hartley;
+#[macro_use]
+mod walsh;
+#[macro_use]
+mod goertzel;
+#[macro_use]
+mod sliding;
+#[macro_use]
+mod convolution;
+#[macro_use]
+mod correlation;
+#[macro_use]
+mod stft;
+#[macro_use]
+mod welch;
+#[macro_use]
+mod windows;
 
 use crate::tables::*;
 
 pub type Angle = u32;
 
+// Order of the Walsh-Hadamard transform output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WhtOrder {
+    // natural (Hadamard) order, as produced by the butterflies
+    Natural,
+    // sorted by the number of sign changes of the Walsh functions
+    Sequency,
+    // bit reversed natural order (Paley)
+    Dyadic,
+}
+
+// Normalization of an autocorrelation estimate over N samples
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Estimator {
+    // r[m] / N, smaller at large lags but never exceeds r[0]
+    Biased,
+    // r[m] / (N - m), the mean of the products at each lag
+    Unbiased,
+}
+
+// Window functions, see windows.rs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Window {
+    Hann,
+    Hamming,
+    // 4 term Blackman-Harris, sidelobes below -92 dB
+    BlackmanHarris,
+    // 5 term flat-top, amplitude error below 0.01 dB between the bins
+    FlatTop,
+    // Kaiser window with the shape parameter beta * 256, at most 32 * 256.
+    // For example 2202 gives beta = 8.6, with sidelobes near -63 dB.
+    Kaiser(u16),
+}
+
 #[cfg(feature = "narrow_index_type")]
 type Index = u16;
 #[cfg(not(feature = "narrow_index_type"))]
@@ -101,6 +163,46 @@ generic_fn_variant! {
     bit_reverse_reorder<T, const N: usize>(data: &mut [T; N])
 }
 
+// bit_reverse_reorder_dyn applied to the `len` elements found
+// at offset, offset + stride, offset + 2 * stride, ...
+pub fn bit_reverse_reorder_strided<T>(data: &mut [T], offset: usize, stride: usize, len: usize) {
+    debug_assert!(len.is_power_of_two());
+    if len < 2 {
+        return;
+    }
+    let shift = usize::BITS - len.trailing_zeros();
+    for i in 0..len {
+        let j = i.reverse_bits() >> shift;
+        if j > i {
+            data.swap(offset + i * stride, offset + j * stride);
+        }
+    }
+}
+
+// Moves the element at `source(pos)` to `pos` for every position. The permutation
+// doesn't have to be an involution, so unlike bit_reverse_reorder every cycle
+// is rotated once, starting from its smallest index.
+pub(crate) fn reorder_dyn<T>(data: &mut [T], source: impl Fn(usize) -> usize) {
+    for start in 0..data.len() {
+        let mut pos = source(start);
+        while pos > start {
+            pos = source(pos);
+        }
+        if pos < start {
+            continue;
+        }
+        let mut dst = start;
+        loop {
+            let src = source(dst);
+            if src == start {
+                break;
+            }
+            data.swap(dst, src);
+            dst = src;
+        }
+    }
+}
+
 generic_fn_variant!{
     pub const? fn interleave_dyn<T>(arr: &mut [(T, T)]) {
         use core::mem::swap;
@@ -123,6 +225,45 @@ generic_fn_variant!{
     interleave<T, const N: usize>(arr: &mut [(T, T); N])
 }
 
+// Reverses interleave
+generic_fn_variant!{
+    pub fn deinterleave_dyn<T>(arr: &mut [(T, T)]) {
+        use core::mem::swap;
+
+        for j in 0..arr.len() / 2 {
+            let j = j * 2;
+            let (a, b) = arr.split_at_mut(j + 1);
+            swap(&mut a[j].1, &mut b[0].0)
+        }
+        let top = arr.len().trailing_zeros() - 2;
+        for log2 in 0..=top {
+            for j in 0..(1 << (top - log2)) {
+                let j = j * 4;
+                arr[(j + 1) << log2..(j + 3) << log2].rotate_right(1 << log2);
+            }
+        }
+    }
+    deinterleave<T, const N: usize>(arr: &mut [(T, T); N])
+}
+
+// Number of values the scratch buffer of fft_any_dyn has to hold
+// for a transform of `len` points
+pub const fn fft_any_scratch_len(len: usize) -> usize {
+    if len < 2 || len.is_power_of_two() {
+        0
+    }
+    else {
+        2 * (2 * len - 1).next_power_of_two()
+    }
+}
+
+// Number of values the scratch buffer of czt_dyn has to hold
+// for `len` input samples and `bins` output frequencies
+pub const fn czt_scratch_len(len: usize, bins: usize) -> usize {
+    let size = (len + bins - 1).next_power_of_two();
+    2 * if size < 2 { 2 } else { size }
+}
+
 // Angle represens an angle in range [0, pi)
 // other angles are not used in this fft implementation
 const fn sin_cos(angle: Angle) -> (TrigTableType, TrigTableType) {
@@ -152,17 +293,203 @@ const fn sin_cos(angle: Angle) -> (TrigTableType, TrigTableType) {
     }
 }
 
+// Helpers for the passes performed outside of the fft core,
+// instantiated in every type module.
+macro_rules! arith_impl {
+    (float; $t:ty;) => {
+        type Twiddle = ($t, $t);
+
+        // (re, im) of e^(-i * angle)
+        const fn twiddle(angle: crate::Angle) -> Twiddle {
+            let (sin, cos) = crate::sin_cos(angle);
+            (
+                cos as $t / (crate::TrigTableType::MAX as $t),
+                sin as $t / (crate::TrigTableType::MAX as $t),
+            )
+        }
+
+        // (re, im) of e^(-2 pi i * turn / 2^32)
+        fn twiddle_turn(turn: u32) -> Twiddle {
+            let w = twiddle(turn << 1);
+            if turn >> 31 != 0 { (-w.0, -w.1) } else { w }
+        }
+
+        // (re, im) of e^(-2 pi i * k / n)
+        fn twiddle_ratio(k: usize, n: usize) -> Twiddle {
+            twiddle_turn(((((k % n) as u64) << 32) / n as u64) as u32)
+        }
+
+        const fn mul(x: ($t, $t), w: Twiddle) -> ($t, $t) {
+            (x.0 * w.0 - x.1 * w.1, x.0 * w.1 + x.1 * w.0)
+        }
+
+        fn mul_real(x: ($t, $t), c: $t) -> ($t, $t) {
+            (x.0 * c, x.1 * c)
+        }
+
+        fn mul_samples(x: ($t, $t), y: ($t, $t)) -> ($t, $t) {
+            (x.0 * y.0 - x.1 * y.1, x.0 * y.1 + x.1 * y.0)
+        }
+
+        fn twiddle_to_sample(w: Twiddle) -> ($t, $t) {
+            w
+        }
+    };
+
+    (int; $t:ty; $wide:ty) => {
+        type Twiddle = ($wide, $wide);
+
+        // (re, im) of e^(-i * angle)
+        const fn twiddle(angle: crate::Angle) -> Twiddle {
+            let (sin, cos) = crate::sin_cos(angle);
+            let shift = crate::TrigTableType::BITS.saturating_sub((0 as $t).count_zeros());
+            ((cos >> shift) as $wide, (sin >> shift) as $wide)
+        }
+
+        // twiddle factors are fixed point numbers with this many fractional bits
+        const MUL_SHIFT: u32 = {
+            let (a, b) = ((1 as crate::TrigTableType).count_zeros(), (1 as $t).count_zeros());
+            if a < b { a } else { b }
+        };
+
+        // (re, im) of e^(-2 pi i * turn / 2^32)
+        fn twiddle_turn(turn: u32) -> Twiddle {
+            let w = twiddle(turn << 1);
+            if turn >> 31 != 0 { (-w.0, -w.1) } else { w }
+        }
+
+        // (re, im) of e^(-2 pi i * k / n)
+        fn twiddle_ratio(k: usize, n: usize) -> Twiddle {
+            twiddle_turn(((((k % n) as u64) << 32) / n as u64) as u32)
+        }
+
+        // num / den as a twiddle component, num <= den
+        fn ratio(num: u64, den: u64) -> $wide {
+            ((num << MUL_SHIFT) / den) as $wide
+        }
+
+        const fn mul(x: ($t, $t), w: Twiddle) -> ($t, $t) {
+            (
+                (((x.0 as $wide * w.0) - (x.1 as $wide * w.1)) >> MUL_SHIFT) as $t,
+                (((x.0 as $wide * w.1) + (x.1 as $wide * w.0)) >> MUL_SHIFT) as $t,
+            )
+        }
+
+        fn mul_real(x: ($t, $t), c: $wide) -> ($t, $t) {
+            (((x.0 as $wide * c) >> MUL_SHIFT) as $t, ((x.1 as $wide * c) >> MUL_SHIFT) as $t)
+        }
+
+        // The twiddle factor with MUL_SHIFT fractional bits. Components of -1 are
+        // rounded down a step, so a negated one has to saturate.
+        fn twiddle_to_sample(w: Twiddle) -> ($t, $t) {
+            let max = <$t>::MAX as $wide;
+            (w.0.min(max) as $t, w.1.min(max) as $t)
+        }
+
+        // Product of two samples, both taken as fixed point numbers in (-1, 1).
+        // The result is exact up to rounding as long as both factors
+        // have a redundant sign bit.
+        fn mul_samples(x: ($t, $t), y: ($t, $t)) -> ($t, $t) {
+            let shift = <$t>::BITS - 1;
+            (
+                (((x.0 as $wide * y.0 as $wide) - (x.1 as $wide * y.1 as $wide)) >> shift) as $t,
+                (((x.0 as $wide * y.1 as $wide) + (x.1 as $wide * y.0 as $wide)) >> shift) as $t,
+            )
+        }
+
+        const fn shr(x: ($t, $t), shift: u32) -> ($t, $t) {
+            (x.0 >> shift, x.1 >> shift)
+        }
+
+        fn shr_each<'a>(data: impl Iterator<Item = &'a mut ($t, $t)>, shift: u32) {
+            if shift != 0 {
+                for x in data {
+                    *x = shr(*x, shift);
+                }
+            }
+        }
+
+        // Shifts the data right until every value has at least `headroom`
+        // redundant sign bits, returns the number of bits shifted.
+        fn normalize_dyn(data: &mut [($t, $t)], headroom: u32) -> i16 {
+            let mut bits: $t = 0;
+            for x in data.iter() {
+                bits |= (x.0 ^ (x.0 >> (<$t>::BITS - 1))) | (x.1 ^ (x.1 >> (<$t>::BITS - 1)));
+            }
+            let shift = headroom.saturating_sub(bits.leading_zeros() - 1);
+            if shift != 0 {
+                for x in data.iter_mut() {
+                    x.0 >>= shift;
+                    x.1 >>= shift;
+                }
+            }
+            shift as i16
+        }
+
+        // Shifts the data left until some value has only `headroom` redundant
+        // sign bits, returns the number of bits shifted.
+        fn expand_dyn(data: &mut [($t, $t)], headroom: u32) -> i16 {
+            let mut bits: $t = 0;
+            for x in data.iter() {
+                bits |= (x.0 ^ (x.0 >> (<$t>::BITS - 1))) | (x.1 ^ (x.1 >> (<$t>::BITS - 1)));
+            }
+            let shift = if bits == 0 { 0 } else { (bits.leading_zeros() - 1).saturating_sub(headroom) };
+            if shift != 0 {
+                for x in data.iter_mut() {
+                    x.0 <<= shift;
+                    x.1 <<= shift;
+                }
+            }
+            shift as i16
+        }
+
+        // Shifts the data right until every component is below
+        // limit / 2^16 * 2^(BITS - 1), returns the number of bits shifted.
+        const fn limit_dyn(data: &mut [($t, $t)], limit: $wide) -> i16 {
+            let mut max: $t = 0;
+            let mut i = 0;
+            while i < data.len() {
+                // magnitudes, one less for negative values
+                let (re, im) = data[i];
+                let re = re ^ (re >> (<$t>::BITS - 1));
+                let im = im ^ (im >> (<$t>::BITS - 1));
+                if re > max { max = re; }
+                if im > max { max = im; }
+                i += 1;
+            }
+            let limit = ((((1 as $wide) << (<$t>::BITS - 1)) * limit) >> 16) as $t;
+            let mut shift = 0;
+            while max >> shift >= limit {
+                shift += 1;
+            }
+            if shift != 0 {
+                let mut i = 0;
+                while i < data.len() {
+                    data[i] = shr(data[i], shift);
+                    i += 1;
+                }
+            }
+            shift as i16
+        }
+
+        // x = m * 2^e with m in [2^31, 2^32)
+        fn split_exponent(x: u128) -> (u64, i32) {
+            let bits = (u128::BITS - x.leading_zeros()) as i32;
+            if bits > 32 { ((x >> (bits - 32)) as u64, bits - 32) } else { ((x << (32 - bits)) as u64, bits - 32) }
+        }
+    };
+}
 
 macro_rules! fft_impl {
     (
-        float; $t:ty; $len:expr;
+        float; $t:ty; $len:expr; $inverse:literal;
         ($($arg:ident: $arg_type:ty),*);
         $x:ident; $x_re:expr; $x_im:expr;
         $y:ident; $y_re:expr; $y_im:expr;
         fn $($signature:tt)*
     ) => {
         fft_impl!(
-            $len,
+            $len, $inverse,
             loop_init: let (mut twiddle_re, mut twiddle_im) = (1., 0.),
             multiply: {
                 let product_re = twiddle_re * $y_re - twiddle_im * $y_im;
@@ -174,9 +501,23 @@ macro_rules! fft_impl {
             },
             next_twiddle: |angle| {
                 let (sin, cos) = crate::sin_cos(angle);
+                // the inverse transform uses conjugated twiddle factors
+                let sin = if $inverse { -sin } else { sin };
                 twiddle_re = cos as $t / (crate::TrigTableType::MAX as $t);
                 twiddle_im = sin as $t / (crate::TrigTableType::MAX as $t);
             },
+            finish: {
+                // scale by 1/N so that a forward and inverse transform cancel out
+                if $inverse {
+                    let scale = 1. / ($len as $t);
+                    let mut $x = 0;
+                    while $x < $len {
+                        $x_re *= scale;
+                        $x_im *= scale;
+                        $x += 1;
+                    }
+                }
+            },
             ($($arg: $arg_type),*);
             $x; $x_re; $x_im;
             $y; $y_re; $y_im;
@@ -185,24 +526,30 @@ macro_rules! fft_impl {
     };
 
     (
-        int; $t:ty; $wide:ty; $len:expr;
+        int; $t:ty; $wide:ty; $len:expr; $inverse:literal;
         ($($arg:ident: $arg_type:ty),*) $(-> $ret:ident: $ret_type:ty)?;
         $x:ident; $x_re:expr; $x_im:expr;
         $y:ident; $y_re:expr; $y_im:expr;
         $($signature:tt)*
     ) => {
         fft_impl!(
-            $len,
+            $len, $inverse,
             loop_init: let (mut twiddle_re, mut twiddle_im, scale) = {
-                let mut scale = 0;
+                let mut max = 0 as $t;
 
                 let mut $x = 0;
                 while $x < $len {
-                    let combined = $x_re as $wide | (($x_im as $wide) << (0 as $t).count_zeros());
-                    scale |= combined ^ (combined << 1);
+                    // magnitudes, one less for negative values
+                    let re = $x_re ^ ($x_re >> ((0 as $t).count_zeros() - 1));
+                    let im = $x_im ^ ($x_im >> ((0 as $t).count_zeros() - 1));
+                    if re > max { max = re; }
+                    if im > max { max = im; }
                     $x += 1;
                 };
-                let scale = ((scale >> (1 as $wide).count_zeros()) | (scale >> (1 as $t).count_zeros())) & 1;
+                // a butterfly can grow a component 1 + sqrt(2) times, so the values are
+                // halved until they are below (sqrt(2) - 1) * 2^(BITS - 1), 27131 / 2^16 here
+                let limit = ((((1 as $wide) << ((0 as $t).count_zeros() - 1)) * 27131) >> 16) as $t;
+                let scale: u32 = if max >= 2 * limit { 2 } else if max >= limit { 1 } else { 0 };
 
                 $($ret += (scale as $ret_type))?;
 
@@ -214,10 +561,10 @@ macro_rules! fft_impl {
                 let (a, b) = ((1 as crate::TrigTableType).count_zeros(), (1 as $t).count_zeros());
                 let shift = if a < b { a } else { b };
                 if scale != 0 {
-                    $y_re >>= 1;
-                    $y_im >>= 1;
-                    $x_re >>= 1;
-                    $x_im >>= 1;
+                    $y_re >>= scale;
+                    $y_im >>= scale;
+                    $x_re >>= scale;
+                    $x_im >>= scale;
                     let product_re = ((($y_re as $wide * twiddle_re) - ($y_im as $wide * twiddle_im)) >> shift) as $t;
                     let product_im = ((($y_im as $wide * twiddle_re) + ($y_re as $wide * twiddle_im)) >> shift) as $t;
                     $y_re = $x_re - product_re;
@@ -236,10 +583,17 @@ macro_rules! fft_impl {
             },
             next_twiddle: |angle| {
                 let (sin, cos) = crate::sin_cos(angle);
+                let sin = if $inverse { -sin } else { sin };
                 let shift = crate::TrigTableType::BITS.saturating_sub((0 as $t).count_zeros());
                 twiddle_re = (cos >> shift) as $wide;
                 twiddle_im = (sin >> shift) as $wide;
             },
+            finish: {
+                // the 1/N factor of the inverse transform only affects the exponent
+                if $inverse {
+                    $($ret -= ($len).trailing_zeros() as $ret_type;)?
+                }
+            },
             ($($arg: $arg_type),*) $(-> $ret: $ret_type)?;
             $x; $x_re; $x_im;
             $y; $y_re; $y_im;
@@ -248,10 +602,11 @@ macro_rules! fft_impl {
     };
 
     (
-        $len:expr,
+        $len:expr, $inverse:literal,
         loop_init: $loop_init:stmt,
         multiply: $mul:block,
         next_twiddle: |$angle:ident| $next_twiddle:block,
+        finish: $finish:block,
         ($($arg:ident: $arg_type:ty),*) $(-> $ret:ident: $ret_type:ty)?;
         $x:ident; $x_re:expr; $x_im:expr;
         $y:ident; $y_re:expr; $y_im:expr;
@@ -287,6 +642,84 @@ macro_rules! fft_impl {
             step_log2 += 1;
             step = 1 << step_log2;
         }
+        $finish
+        $($ret)?
+    }
+
+    };
+}
+
+// Passes between the spectrum of the `N` pairs (x[2k], x[2k + 1]) and the first
+// half of the spectrum of the `2 * N` real samples x. X[N] is real, so it's
+// stored in the imaginary part of the DC bin: (X[0], X[N]).
+macro_rules! real_impl {
+    (
+        $t:ty; $div2:expr; $len:expr; $inverse:literal;
+        ($($arg:ident: $arg_type:ty),*) $(-> $ret:ident: $ret_type:ty)?;
+        $x:ident; $x_re:expr; $x_im:expr;
+        $y:ident; $y_re:expr; $y_im:expr;
+        fn $name:ident
+    ) => {
+
+    fn $name($($arg: $arg_type),* $(, mut $ret: $ret_type)?) $(-> $ret_type)? {
+        let len = $len;
+        let log2 = len.trailing_zeros();
+
+        // leave enough headroom for the butterflies below not to overflow
+        $({
+            let mut bits: $t = 0;
+            let mut $x = 0;
+            while $x < len {
+                bits |= ($x_re ^ ($x_re >> (<$t>::BITS - 1))) | ($x_im ^ ($x_im >> (<$t>::BITS - 1)));
+                $x += 1;
+            }
+            let shift = 2_u32.saturating_sub(bits.leading_zeros() - 1);
+            if shift != 0 {
+                let mut $x = 0;
+                while $x < len {
+                    $x_re >>= shift;
+                    $x_im >>= shift;
+                    $x += 1;
+                }
+            }
+            $ret += shift as $ret_type;
+        })?
+
+        let $x = 0;
+        let (re, im) = ($x_re, $x_im);
+        if $inverse {
+            $x_re = $div2(re) + $div2(im);
+            $x_im = $div2(re) - $div2(im);
+        }
+        else {
+            $x_re = re + im;
+            $x_im = re - im;
+        }
+
+        let mut k = 1;
+        while k <= len / 2 {
+            let ($x, $y) = (k, len - k);
+            let w = twiddle((k as crate::Angle) << (crate::Angle::BITS - log2));
+            if $inverse {
+                let even = ($div2($x_re) + $div2($y_re), $div2($x_im) - $div2($y_im));
+                let odd = ($div2($x_re) - $div2($y_re), $div2($x_im) + $div2($y_im));
+                let odd = mul(odd, (w.0, -w.1));
+                $x_re = even.0 - odd.1;
+                $x_im = even.1 + odd.0;
+                $y_re = even.0 + odd.1;
+                $y_im = odd.0 - even.1;
+            }
+            else {
+                let even = ($div2($x_re) + $div2($y_re), $div2($x_im) - $div2($y_im));
+                let odd = ($div2($x_im) + $div2($y_im), $div2($y_re) - $div2($x_re));
+                let odd = mul(odd, w);
+                $x_re = even.0 + odd.0;
+                $x_im = even.1 + odd.1;
+                $y_re = even.0 - odd.0;
+                $y_im = odd.1 - even.1;
+            }
+            k += 1;
+        }
         $($ret)?
     }
 
@@ -295,8 +728,10 @@ macro_rules! fft_impl {
 
 macro_rules! type_impl {
     ($kind:tt; $div2:expr; $($ret:ident = $ret_init:literal: $ret_type:ty)?; $mod:ident, $t:ty, $($wide:ty)? $(,$qualifier:tt)?) => { pub mod $mod {
+    arith_impl!($kind; $t; $($wide)?);
+
     fft_impl!(
-        $kind; $t; $($wide;)? N;
+        $kind; $t; $($wide;)? N; false;
         (data: &mut [($t, $t); N]) $(-> $ret: $ret_type)?;
         a; data[a].0; data[a].1;
         b; data[b].0; data[b].1;
@@ -304,7 +739,7 @@ macro_rules! type_impl {
     );
 
     fft_impl!(
-        $kind; $t; $($wide;)? data.len();
+        $kind; $t; $($wide;)? data.len(); false;
         (data: &mut [($t, $t)]) $(-> $ret: $ret_type)?;
         a; data[a].0; data[a].1;
         b; data[b].0; data[b].1;
@@ -312,13 +747,69 @@ macro_rules! type_impl {
     );
 
     fft_impl!(
-        $kind; $t; $($wide;)? N;
+        $kind; $t; $($wide;)? N; false;
         (re: &mut [$t; N], im: &mut [$t; N]) $(-> $ret: $ret_type)?;
         a; re[a]; im[a];
         b; re[b]; im[b];
         $($qualifier)? fn compute_arrays<const N: usize>
     );
 
+    fft_impl!(
+        $kind; $t; $($wide;)? re.len(); false;
+        (re: &mut [$t], im: &mut [$t]) $(-> $ret: $ret_type)?;
+        a; re[a]; im[a];
+        b; re[b]; im[b];
+        $($qualifier)? fn compute_arrays_dyn
+    );
+
+    fft_impl!(
+        $kind; $t; $($wide;)? len; false;
+        (data: &mut [($t, $t)], offset: usize, stride: usize, len: usize) $(-> $ret: $ret_type)?;
+        a; data[offset + a * stride].0; data[offset + a * stride].1;
+        b; data[offset + b * stride].0; data[offset + b * stride].1;
+        $($qualifier)? fn compute_pairs_strided_dyn
+    );
+
+    fft_impl!(
+        $kind; $t; $($wide;)? len; true;
+        (data: &mut [($t, $t)], offset: usize, stride: usize, len: usize) $(-> $ret: $ret_type)?;
+        a; data[offset + a * stride].0; data[offset + a * stride].1;
+        b; data[offset + b * stride].0; data[offset + b * stride].1;
+        $($qualifier)? fn compute_pairs_strided_inverse_dyn
+    );
+
+    fft_impl!(
+        $kind; $t; $($wide;)? N; true;
+        (data: &mut [($t, $t); N]) $(-> $ret: $ret_type)?;
+        a; data[a].0; data[a].1;
+        b; data[b].0; data[b].1;
+        $($qualifier)? fn compute_pairs_inverse<const N: usize>
+    );
+
+    fft_impl!(
+        $kind; $t; $($wide;)? data.len(); true;
+        (data: &mut [($t, $t)]) $(-> $ret: $ret_type)?;
+        a; data[a].0; data[a].1;
+        b; data[b].0; data[b].1;
+        $($qualifier)? fn compute_pairs_inverse_dyn
+    );
+
+    fft_impl!(
+        $kind; $t; $($wide;)? N; true;
+        (re: &mut [$t; N], im: &mut [$t; N]) $(-> $ret: $ret_type)?;
+        a; re[a]; im[a];
+        b; re[b]; im[b];
+        $($qualifier)? fn compute_arrays_inverse<const N: usize>
+    );
+
+    fft_impl!(
+        $kind; $t; $($wide;)? re.len(); true;
+        (re: &mut [$t], im: &mut [$t]) $(-> $ret: $ret_type)?;
+        a; re[a]; im[a];
+        b; re[b]; im[b];
+        $($qualifier)? fn compute_arrays_inverse_dyn
+    );
+
     generic_fn_variant!{
         pub const? fn rfft_twice_postprocess_dyn(arr: &mut [($t, $t)]) {
             use core::mem::swap;
@@ -344,6 +835,30 @@ macro_rules! type_impl {
         rfft_twice_postprocess<const N: usize>(arr: &mut [($t, $t); N])
     }
 
+    // Reverses rfft_twice_postprocess, combining the two half spectra
+    // back into the spectrum of x + iy.
+    fn irfft_twice_preprocess_dyn(arr: &mut [($t, $t)] $(, mut $ret: $ret_type)?) $(-> $ret_type)? {
+        use core::mem::swap;
+
+        $($ret += normalize_dyn(arr, 1);)?
+
+        let mid = arr.len() / 2;
+        let (a, b) = arr.split_at_mut(mid);
+        for i in 1..b.len() / 2 {
+            b.swap(i, b.len() - i);
+        }
+
+        swap(&mut a[0].1, &mut b[0].0);
+        for (x, y) in a[1..].iter_mut().zip(b.iter_mut().rev()) {
+            let (x_re, x_im, y_re, y_im) = (x.0, x.1, y.0, y.1);
+            x.0 = x_re - y_im;
+            x.1 = x_im + y_re;
+            y.0 = x_re + y_im;
+            y.1 = y_re - x_im;
+        }
+        $($ret)?
+    }
+
     pub $($qualifier)? fn rfft_pairs_twice<const N: usize>(data: &mut [($t, $t); N]) $(-> $ret_type)? {
         debug_assert!(data.len().is_power_of_two());
         super::interleave(data);
@@ -360,6 +875,302 @@ macro_rules! type_impl {
         ret
     }
 
+    radix4_impl!(
+        $kind; $t; $($wide;)? N; false;
+        (data: &mut [($t, $t); N]) $(-> $ret: $ret_type)?;
+        data; a; data[a].0; data[a].1;
+        $($qualifier)? fn compute_pairs_radix4<const N: usize>
+    );
+
+    radix4_impl!(
+        $kind; $t; $($wide;)? data.len(); false;
+        (data: &mut [($t, $t)]) $(-> $ret: $ret_type)?;
+        data; a; data[a].0; data[a].1;
+        $($qualifier)? fn compute_pairs_radix4_dyn
+    );
+
+    radix4_impl!(
+        $kind; $t; $($wide;)? N; true;
+        (data: &mut [($t, $t); N]) $(-> $ret: $ret_type)?;
+        data; a; data[a].0; data[a].1;
+        $($qualifier)? fn compute_pairs_radix4_inverse<const N: usize>
+    );
+
+    radix4_impl!(
+        $kind; $t; $($wide;)? data.len(); true;
+        (data: &mut [($t, $t)]) $(-> $ret: $ret_type)?;
+        data; a; data[a].0; data[a].1;
+        $($qualifier)? fn compute_pairs_radix4_inverse_dyn
+    );
+
+    split_radix_impl!(
+        $kind; $t; $($wide;)? N; false;
+        (data: &mut [($t, $t); N]) $(-> $ret: $ret_type)?;
+        data; a; data[a].0; data[a].1;
+        $($qualifier)? fn compute_pairs_split_radix<const N: usize>
+    );
+
+    split_radix_impl!(
+        $kind; $t; $($wide;)? data.len(); false;
+        (data: &mut [($t, $t)]) $(-> $ret: $ret_type)?;
+        data; a; data[a].0; data[a].1;
+        $($qualifier)? fn compute_pairs_split_radix_dyn
+    );
+
+    split_radix_impl!(
+        $kind; $t; $($wide;)? N; true;
+        (data: &mut [($t, $t); N]) $(-> $ret: $ret_type)?;
+        data; a; data[a].0; data[a].1;
+        $($qualifier)? fn compute_pairs_split_radix_inverse<const N: usize>
+    );
+
+    split_radix_impl!(
+        $kind; $t; $($wide;)? data.len(); true;
+        (data: &mut [($t, $t)]) $(-> $ret: $ret_type)?;
+        data; a; data[a].0; data[a].1;
+        $($qualifier)? fn compute_pairs_split_radix_inverse_dyn
+    );
+
+    mixed_radix_impl!(butterfly; $t; $div2);
+
+    mixed_radix_impl!(
+        $kind; $t; $($wide;)? N; false;
+        (data: &mut [($t, $t); N]) $(-> $ret: $ret_type)?;
+        data; a; data[a].0; data[a].1;
+        fn compute_pairs_mixed_radix<const N: usize>
+    );
+
+    mixed_radix_impl!(
+        $kind; $t; $($wide;)? data.len(); false;
+        (data: &mut [($t, $t)]) $(-> $ret: $ret_type)?;
+        data; a; data[a].0; data[a].1;
+        fn compute_pairs_mixed_radix_dyn
+    );
+
+    mixed_radix_impl!(
+        $kind; $t; $($wide;)? N; true;
+        (data: &mut [($t, $t); N]) $(-> $ret: $ret_type)?;
+        data; a; data[a].0; data[a].1;
+        fn compute_pairs_mixed_radix_inverse<const N: usize>
+    );
+
+    mixed_radix_impl!(
+        $kind; $t; $($wide;)? data.len(); true;
+        (data: &mut [($t, $t)]) $(-> $ret: $ret_type)?;
+        data; a; data[a].0; data[a].1;
+        fn compute_pairs_mixed_radix_inverse_dyn
+    );
+
+    fht_impl!(
+        $kind; $t; $($wide;)? N;
+        (data: &mut [$t; N]) $(-> $ret: $ret_type)?;
+        a; data[a];
+        $($qualifier)? fn compute_hartley<const N: usize>
+    );
+
+    fht_impl!(
+        $kind; $t; $($wide;)? data.len();
+        (data: &mut [$t]) $(-> $ret: $ret_type)?;
+        a; data[a];
+        $($qualifier)? fn compute_hartley_dyn
+    );
+
+    wht_impl!(
+        $kind; $t; N;
+        (data: &mut [$t; N]) $(-> $ret: $ret_type)?;
+        a; data[a];
+        b; data[b];
+        $($qualifier)? fn compute_walsh<const N: usize>
+    );
+
+    wht_impl!(
+        $kind; $t; data.len();
+        (data: &mut [$t]) $(-> $ret: $ret_type)?;
+        a; data[a];
+        b; data[b];
+        $($qualifier)? fn compute_walsh_dyn
+    );
+
+    batch_impl!(
+        $kind; $t; $($ret_init: $ret_type;)?
+        (data, channels $(, $ret)?);
+        fn compute_pairs_batch_dyn
+    );
+
+    real_impl!(
+        $t; $div2; data.len(); false;
+        (data: &mut [($t, $t)]) $(-> $ret: $ret_type)?;
+        a; data[a].0; data[a].1;
+        b; data[b].0; data[b].1;
+        fn rfft_postprocess_dyn
+    );
+
+    real_impl!(
+        $t; $div2; data.len(); true;
+        (data: &mut [($t, $t)]) $(-> $ret: $ret_type)?;
+        a; data[a].0; data[a].1;
+        b; data[b].0; data[b].1;
+        fn irfft_preprocess_dyn
+    );
+
+    real_impl!(
+        $t; $div2; re.len(); false;
+        (re: &mut [$t], im: &mut [$t]) $(-> $ret: $ret_type)?;
+        a; re[a]; im[a];
+        b; re[b]; im[b];
+        fn rfft_postprocess_arrays_dyn
+    );
+
+    real_impl!(
+        $t; $div2; re.len(); true;
+        (re: &mut [$t], im: &mut [$t]) $(-> $ret: $ret_type)?;
+        a; re[a]; im[a];
+        b; re[b]; im[b];
+        fn irfft_preprocess_arrays_dyn
+    );
+
+    pub fn rfft<const N: usize>(data: &mut [($t, $t); N]) $(-> $ret_type)? {
+        debug_assert!(N.is_power_of_two());
+        $(let $ret: $ret_type =)? fft_pairs(data);
+        rfft_postprocess_dyn(data $(, $ret)?)
+    }
+
+    pub fn rfft_dyn(data: &mut [($t, $t)]) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        $(let $ret: $ret_type =)? fft_pairs_dyn(data);
+        rfft_postprocess_dyn(data $(, $ret)?)
+    }
+
+    // Same as rfft, with x[2k] stored in data_re[k] and x[2k + 1] in data_im[k]
+    pub fn rfft_arrays<const N: usize>(data_re: &mut [$t; N], data_im: &mut [$t; N]) $(-> $ret_type)? {
+        debug_assert!(N.is_power_of_two());
+        $(let $ret: $ret_type =)? fft_arrays(data_re, data_im);
+        rfft_postprocess_arrays_dyn(data_re, data_im $(, $ret)?)
+    }
+
+    pub fn rfft_arrays_dyn(data_re: &mut [$t], data_im: &mut [$t]) $(-> $ret_type)? {
+        debug_assert!(data_re.len().is_power_of_two());
+        $(let $ret: $ret_type =)? fft_arrays_dyn(data_re, data_im);
+        rfft_postprocess_arrays_dyn(data_re, data_im $(, $ret)?)
+    }
+
+    // Inverse of rfft, takes the half spectrum in the same layout
+    // and writes the real samples back as pairs (x[2k], x[2k + 1]).
+    pub fn irfft<const N: usize>(data: &mut [($t, $t); N] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(N.is_power_of_two());
+        $(let $ret: $ret_type =)? irfft_preprocess_dyn(data $(, $ret)?);
+        ifft_pairs(data $(, $ret)?)
+    }
+
+    pub fn irfft_dyn(data: &mut [($t, $t)] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        $(let $ret: $ret_type =)? irfft_preprocess_dyn(data $(, $ret)?);
+        ifft_pairs_dyn(data $(, $ret)?)
+    }
+
+    pub fn irfft_arrays<const N: usize>(data_re: &mut [$t; N], data_im: &mut [$t; N] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(N.is_power_of_two());
+        $(let $ret: $ret_type =)? irfft_preprocess_arrays_dyn(data_re, data_im $(, $ret)?);
+        ifft_arrays(data_re, data_im $(, $ret)?)
+    }
+
+    pub fn irfft_arrays_dyn(data_re: &mut [$t], data_im: &mut [$t] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data_re.len().is_power_of_two());
+        $(let $ret: $ret_type =)? irfft_preprocess_arrays_dyn(data_re, data_im $(, $ret)?);
+        ifft_arrays_dyn(data_re, data_im $(, $ret)?)
+    }
+
+    // Inverse of rfft_pairs_twice, recovers both real signals
+    // in the layout expected by rfft_pairs_twice.
+    pub fn irfft_pairs_twice<const N: usize>(data: &mut [($t, $t); N] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        $(let $ret: $ret_type =)? irfft_twice_preprocess_dyn(data $(, $ret)?);
+        $(let $ret: $ret_type =)? ifft_pairs(data $(, $ret)?);
+        super::deinterleave(data);
+        $($ret)?
+    }
+
+    pub fn irfft_pairs_twice_dyn(data: &mut [($t, $t)] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        $(let $ret: $ret_type =)? irfft_twice_preprocess_dyn(data $(, $ret)?);
+        $(let $ret: $ret_type =)? ifft_pairs_dyn(data $(, $ret)?);
+        super::deinterleave_dyn(data);
+        $($ret)?
+    }
+
+    // Hartley transform of the real samples
+    pub $($qualifier)? fn fht<const N: usize>(data: &mut [$t; N]) $(-> $ret_type)? {
+        debug_assert!(N.is_power_of_two());
+        super::bit_reverse_reorder(data);
+        compute_hartley(data $(, $ret_init)?)
+    }
+
+    pub $($qualifier)? fn fht_dyn(data: &mut [$t]) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        super::bit_reverse_reorder_dyn(data);
+        compute_hartley_dyn(data $(, $ret_init)?)
+    }
+
+    // Bin k of the dft of the real samples, from their Hartley transform
+    // X[k] = (H[k] + H[N - k]) / 2 - i * (H[k] - H[N - k]) / 2
+    pub fn fht_bin(data: &[$t], k: usize) -> ($t, $t) {
+        let (a, b) = (data[k], data[(data.len() - k) % data.len()]);
+        ($div2(a) + $div2(b), $div2(b) - $div2(a))
+    }
+
+    // The first out.len() bins of the dft, the rest follows from X[N - k] = conj(X[k])
+    pub fn fht_spectrum_dyn(data: &[$t], out: &mut [($t, $t)]) {
+        debug_assert!(out.len() <= data.len());
+        for (k, bin) in out.iter_mut().enumerate() {
+            *bin = fht_bin(data, k);
+        }
+    }
+
+    // Walsh-Hadamard transform of the real samples, with the output in the given
+    // order. The transform is its own inverse up to a factor of N.
+    pub fn wht<const N: usize>(data: &mut [$t; N], order: crate::WhtOrder) $(-> $ret_type)? {
+        debug_assert!(N.is_power_of_two());
+        if order != crate::WhtOrder::Natural {
+            super::bit_reverse_reorder(data);
+        }
+        $(let $ret: $ret_type =)? compute_walsh(data $(, $ret_init)?);
+        if order == crate::WhtOrder::Sequency {
+            crate::reorder_dyn(data, |k| k ^ (k >> 1));
+        }
+        $($ret)?
+    }
+
+    pub fn wht_dyn(data: &mut [$t], order: crate::WhtOrder) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        // the Hadamard matrix doesn't change when both its rows and columns are
+        // bit reversed, so transforming the bit reversed input gives dyadic order
+        if order != crate::WhtOrder::Natural {
+            super::bit_reverse_reorder_dyn(data);
+        }
+        $(let $ret: $ret_type =)? compute_walsh_dyn(data $(, $ret_init)?);
+        // sequency k is found at dyadic index k ^ (k >> 1)
+        if order == crate::WhtOrder::Sequency {
+            crate::reorder_dyn(data, |k| k ^ (k >> 1));
+        }
+        $($ret)?
+    }
+
+    dct_impl!($kind; $t; $($ret = $ret_init: $ret_type)?);
+
+    goertzel_impl!($kind; $t; $($wide;)? $($ret = $ret_init: $ret_type)?);
+
+    sliding_dft_impl!($kind; $t; $($wide;)? $($ret_init: $ret_type)?);
+
+    convolution_impl!($kind; $t; $($wide;)? $($ret = $ret_init: $ret_type)?);
+
+    correlation_impl!($kind; $t; $($wide;)? $($ret = $ret_init: $ret_type)?);
+
+    stft_impl!($t; $($ret = $ret_init: $ret_type)?);
+
+    welch_impl!($kind; $t; $($wide;)? $($ret = $ret_init: $ret_type)?);
+
+    windows_impl!($kind; $t; $($ret = $ret_init: $ret_type)?; $($qualifier)?);
+
     pub $($qualifier)? fn fft_pairs<const N: usize>(data: &mut [($t, $t); N]) $(-> $ret_type)? {
         debug_assert!(data.len().is_power_of_two());
         super::bit_reverse_reorder(data);
@@ -372,6 +1183,240 @@ macro_rules! type_impl {
         compute_pairs_dyn(data $(, $ret_init)?)
     }
 
+    pub $($qualifier)? fn fft_pairs_radix4<const N: usize>(data: &mut [($t, $t); N]) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        super::bit_reverse_reorder(data);
+        compute_pairs_radix4(data $(, $ret_init)?)
+    }
+
+    pub $($qualifier)? fn fft_pairs_radix4_dyn(data: &mut [($t, $t)]) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        super::bit_reverse_reorder_dyn(data);
+        compute_pairs_radix4_dyn(data $(, $ret_init)?)
+    }
+
+    // Transforms the `len` points at offset, offset + stride, offset + 2 * stride, ...
+    // e.g. a single channel of an interleaved buffer, leaving the rest untouched
+    pub fn fft_pairs_strided(data: &mut [($t, $t)], offset: usize, stride: usize, len: usize) $(-> $ret_type)? {
+        debug_assert!(len.is_power_of_two());
+        debug_assert!(offset + (len - 1) * stride < data.len());
+        super::bit_reverse_reorder_strided(data, offset, stride, len);
+        compute_pairs_strided_dyn(data, offset, stride, len $(, $ret_init)?)
+    }
+
+    // The split-radix transforms reorder the output instead of the input
+    pub $($qualifier)? fn fft_pairs_split_radix<const N: usize>(data: &mut [($t, $t); N]) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        $(let $ret: $ret_type =)? compute_pairs_split_radix(data $(, $ret_init)?);
+        super::bit_reverse_reorder(data);
+        $($ret)?
+    }
+
+    pub $($qualifier)? fn fft_pairs_split_radix_dyn(data: &mut [($t, $t)]) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        $(let $ret: $ret_type =)? compute_pairs_split_radix_dyn(data $(, $ret_init)?);
+        super::bit_reverse_reorder_dyn(data);
+        $($ret)?
+    }
+
+    // The mixed-radix transforms accept sizes of the form 2^a * 3^b * 5^c and
+    // panic for any other size
+    pub fn fft_pairs_mixed_radix<const N: usize>(data: &mut [($t, $t); N]) $(-> $ret_type)? {
+        assert!(crate::mixed_radix::is_supported(N));
+        crate::mixed_radix::digit_reverse_reorder_dyn(data);
+        compute_pairs_mixed_radix(data $(, $ret_init)?)
+    }
+
+    pub fn fft_pairs_mixed_radix_dyn(data: &mut [($t, $t)]) $(-> $ret_type)? {
+        assert!(crate::mixed_radix::is_supported(data.len()));
+        crate::mixed_radix::digit_reverse_reorder_dyn(data);
+        compute_pairs_mixed_radix_dyn(data $(, $ret_init)?)
+    }
+
+    // Circular convolution of `a` with a chirp `b` made of twiddle factors,
+    // using power of two ffts. The result is left in `a`.
+    fn convolve_chirp(a: &mut [($t, $t)], b: &mut [($t, $t)] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        $(let $ret: $ret_type = $ret - $ret_init +)? fft_pairs_dyn(a);
+        $(let b_exp: $ret_type =)? fft_pairs_dyn(b);
+        // b started out as twiddle factors, with MUL_SHIFT fractional bits
+        $(
+            let $ret: $ret_type = $ret + normalize_dyn(a, 1) - expand_dyn(a, 1)
+                + b_exp - $ret_init - MUL_SHIFT as $ret_type + normalize_dyn(b, 1) - expand_dyn(b, 1)
+                + (<$t>::BITS - 1) as $ret_type;
+        )?
+        for (x, y) in a.iter_mut().zip(b.iter()) {
+            *x = mul_samples(*x, *y);
+        }
+        $(let $ret: $ret_type = $ret - expand_dyn(a, 1);)?
+        ifft_pairs_dyn(a $(, $ret)?)
+    }
+
+    // Bluestein's algorithm, a dft of any length expressed as a convolution
+    // with a chirp, which is computed with power of two ffts. `scratch` has to
+    // hold at least fft_any_scratch_len(data.len()) values.
+    pub fn fft_any_dyn(data: &mut [($t, $t)], scratch: &mut [($t, $t)]) $(-> $ret_type)? {
+        let len = data.len();
+        if len < 2 {
+            return $($ret_init)?;
+        }
+        if len.is_power_of_two() {
+            return fft_pairs_dyn(data);
+        }
+        debug_assert!(scratch.len() >= super::fft_any_scratch_len(len));
+
+        let size = (2 * len - 1).next_power_of_two();
+        let (a, b) = scratch[..2 * size].split_at_mut(size);
+        a.fill((0 as $t, 0 as $t));
+        b.fill((0 as $t, 0 as $t));
+
+        // a rotated value can have components sqrt(2) times larger
+        $(let $ret: $ret_type = $ret_init + normalize_dyn(data, 1);)?
+
+        // a[n] = x[n] * w[n] and b[n] = b[-n] = conj(w[n]), with w[n] = e^(-pi i * n^2 / N)
+        let mut square = 0;
+        for n in 0..len {
+            let w = twiddle_ratio(square, 2 * len);
+            a[n] = mul(data[n], w);
+            b[n] = twiddle_to_sample((w.0, -w.1));
+            b[(size - n) % size] = b[n];
+            square = (square + 2 * n + 1) % (2 * len);
+        }
+
+        $(let $ret: $ret_type =)? convolve_chirp(a, b $(, $ret)?);
+        $(let $ret: $ret_type = $ret + normalize_dyn(&mut a[..len], 1);)?
+
+        // X[k] = w[k] * (a * b)[k]
+        let mut square = 0;
+        for k in 0..len {
+            data[k] = mul(a[k], twiddle_ratio(square, 2 * len));
+            square = (square + 2 * k + 1) % (2 * len);
+        }
+        $($ret)?
+    }
+
+    // Chirp-z transform, evaluates the spectrum of `data` at out.len() frequencies
+    // start, start + step, start + 2 * step, ... given as Angles, so 2^32 is half
+    // the sample rate. `scratch` has to hold at least
+    // czt_scratch_len(data.len(), out.len()) values.
+    // A step much smaller than the dft bin spacing makes the chirp slow and its
+    // spectrum narrow, so the inner ffts spend their range on a few bins. The i16
+    // variant then loses precision, its error can grow to about 1% of the output.
+    pub fn czt_dyn(
+        data: &[($t, $t)],
+        out: &mut [($t, $t)],
+        start: crate::Angle,
+        step: crate::Angle,
+        scratch: &mut [($t, $t)],
+    ) $(-> $ret_type)? {
+        let (len, bins) = (data.len(), out.len());
+        debug_assert!(len > 0);
+        debug_assert!(scratch.len() >= super::czt_scratch_len(len, bins));
+
+        let size = (len + bins - 1).next_power_of_two().max(2);
+        let (a, b) = scratch[..2 * size].split_at_mut(size);
+        a.fill((0 as $t, 0 as $t));
+        b.fill((0 as $t, 0 as $t));
+        a[..len].copy_from_slice(data);
+        // A rotated value can have components sqrt(2) times larger. A quiet input
+        // is also scaled up to the full range, the rounding of the inner ffts
+        // would otherwise dominate a narrow band of bins.
+        $(let $ret: $ret_type = $ret_init + normalize_dyn(&mut a[..len], 1) - expand_dyn(&mut a[..len], 1);)?
+
+        // Phases are counted in halves of an Angle, so step * m^2 / 2 is an integer
+        // and a full turn is 2^34, which divides the wrapping range of u64.
+        let turn = |phase: u64| (phase >> 2) as u32;
+
+        // a[m] = x[m] * e^(-i * (start * m + step * m^2 / 2)),
+        // b[m] = b[-m] = e^(i * step * m^2 / 2), as far as the convolution reaches
+        let (mut chirp, mut offset) = (0_u64, 0_u64);
+        for m in 0..len.max(bins) {
+            let w = twiddle_turn(turn(chirp));
+            if m < len {
+                a[m] = mul(a[m], twiddle_turn(turn(chirp.wrapping_add(offset))));
+            }
+            if m < bins {
+                b[m] = twiddle_to_sample((w.0, -w.1));
+            }
+            if m > 0 && m < len {
+                b[size - m] = twiddle_to_sample((w.0, -w.1));
+            }
+            chirp = chirp.wrapping_add((step as u64).wrapping_mul(2 * m as u64 + 1));
+            offset = offset.wrapping_add(2 * start as u64);
+        }
+
+        $(let $ret: $ret_type =)? convolve_chirp(a, b $(, $ret)?);
+        $(let $ret: $ret_type = $ret + normalize_dyn(&mut a[..bins], 1);)?
+
+        // X[k] = e^(-i * step * k^2 / 2) * (a * b)[k]
+        let mut chirp = 0_u64;
+        for k in 0..bins {
+            out[k] = mul(a[k], twiddle_turn(turn(chirp)));
+            chirp = chirp.wrapping_add((step as u64).wrapping_mul(2 * k as u64 + 1));
+        }
+        $($ret)?
+    }
+
+    // Transforms `channels` channels of the same power of two length, stored one
+    // after another in `data`. The integer variants write the exponents to
+    // `lsb_mult_log2`, which holds either one per channel or a single common one.
+    pub fn fft_pairs_batch_dyn(data: &mut [($t, $t)], channels: usize $(, $ret: &mut [$ret_type])?) {
+        debug_assert!(channels > 0 && data.len() % channels == 0);
+        debug_assert!((data.len() / channels).is_power_of_two());
+        $(debug_assert!($ret.len() == 1 || $ret.len() == channels);)?
+        for channel in data.chunks_mut(data.len() / channels) {
+            super::bit_reverse_reorder_dyn(channel);
+        }
+        compute_pairs_batch_dyn(data, channels $(, $ret)?)
+    }
+
+    // 2d fft of a row-major buffer, the rows are transformed first and then
+    // the columns, in place. Both dimensions have to be powers of two.
+    pub fn fft2d_pairs<const R: usize, const C: usize>(data: &mut [[($t, $t); C]; R]) $(-> $ret_type)? {
+        fft2d_pairs_dyn(data.as_flattened_mut(), R, C)
+    }
+
+    pub fn fft2d_pairs_dyn(data: &mut [($t, $t)], rows: usize, cols: usize) $(-> $ret_type)? {
+        debug_assert!(rows.is_power_of_two() && cols.is_power_of_two());
+        debug_assert!(data.len() == rows * cols);
+        $(let mut $ret: $ret_type = $ret_init;)?
+
+        // every row and column gets its own exponent, the ones done so far
+        // are brought to the largest of them, so they all share a single one
+        if cols > 1 {
+            for r in 0..rows {
+                let row = r * cols..(r + 1) * cols;
+                $(let exp: $ret_type =)? fft_pairs_dyn(&mut data[row.clone()]);
+                $(
+                    if exp > $ret {
+                        shr_each(data[..row.start].iter_mut(), (exp - $ret) as u32);
+                        $ret = exp;
+                    }
+                    else {
+                        shr_each(data[row].iter_mut(), ($ret - exp) as u32);
+                    }
+                )?
+            }
+        }
+        if rows > 1 {
+            // the columns start out with the exponent of the rows
+            $(let row_exp: $ret_type = $ret;)?
+            for c in 0..cols {
+                super::bit_reverse_reorder_strided(data, c, cols, rows);
+                $(let exp: $ret_type = row_exp - $ret_init +)? compute_pairs_strided_dyn(data, c, cols, rows $(, $ret_init)?);
+                $(
+                    if exp > $ret {
+                        shr_each(data.chunks_mut(cols).flat_map(|row| row[..c].iter_mut()), (exp - $ret) as u32);
+                        $ret = exp;
+                    }
+                    else {
+                        shr_each(data[c..].iter_mut().step_by(cols), ($ret - exp) as u32);
+                    }
+                )?
+            }
+        }
+        $($ret)?
+    }
+
     pub $($qualifier)? fn fft_arrays<const N: usize>(data_re: &mut [$t; N], data_im: &mut [$t; N]) $(-> $ret_type)? {
         debug_assert!(N.is_power_of_two());
         super::bit_reverse_reorder(data_re);
@@ -379,6 +1424,86 @@ macro_rules! type_impl {
         compute_arrays(data_re, data_im $(, $ret_init)?)
     }
 
+    pub $($qualifier)? fn fft_arrays_dyn(data_re: &mut [$t], data_im: &mut [$t]) $(-> $ret_type)? {
+        debug_assert!(data_re.len().is_power_of_two());
+        debug_assert!(data_re.len() == data_im.len());
+        super::bit_reverse_reorder_dyn(data_re);
+        super::bit_reverse_reorder_dyn(data_im);
+        compute_arrays_dyn(data_re, data_im $(, $ret_init)?)
+    }
+
+    pub $($qualifier)? fn ifft_pairs<const N: usize>(data: &mut [($t, $t); N] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        super::bit_reverse_reorder(data);
+        compute_pairs_inverse(data $(, $ret)?)
+    }
+
+    pub $($qualifier)? fn ifft_pairs_dyn(data: &mut [($t, $t)] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        super::bit_reverse_reorder_dyn(data);
+        compute_pairs_inverse_dyn(data $(, $ret)?)
+    }
+
+    pub fn ifft_pairs_strided(data: &mut [($t, $t)], offset: usize, stride: usize, len: usize $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(len.is_power_of_two());
+        debug_assert!(offset + (len - 1) * stride < data.len());
+        super::bit_reverse_reorder_strided(data, offset, stride, len);
+        compute_pairs_strided_inverse_dyn(data, offset, stride, len $(, $ret)?)
+    }
+
+    pub $($qualifier)? fn ifft_pairs_radix4<const N: usize>(data: &mut [($t, $t); N] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        super::bit_reverse_reorder(data);
+        compute_pairs_radix4_inverse(data $(, $ret)?)
+    }
+
+    pub $($qualifier)? fn ifft_pairs_radix4_dyn(data: &mut [($t, $t)] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        super::bit_reverse_reorder_dyn(data);
+        compute_pairs_radix4_inverse_dyn(data $(, $ret)?)
+    }
+
+    pub $($qualifier)? fn ifft_pairs_split_radix<const N: usize>(data: &mut [($t, $t); N] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        $(let $ret: $ret_type =)? compute_pairs_split_radix_inverse(data $(, $ret)?);
+        super::bit_reverse_reorder(data);
+        $($ret)?
+    }
+
+    pub $($qualifier)? fn ifft_pairs_split_radix_dyn(data: &mut [($t, $t)] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data.len().is_power_of_two());
+        $(let $ret: $ret_type =)? compute_pairs_split_radix_inverse_dyn(data $(, $ret)?);
+        super::bit_reverse_reorder_dyn(data);
+        $($ret)?
+    }
+
+    pub fn ifft_pairs_mixed_radix<const N: usize>(data: &mut [($t, $t); N] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        assert!(crate::mixed_radix::is_supported(N));
+        crate::mixed_radix::digit_reverse_reorder_dyn(data);
+        compute_pairs_mixed_radix_inverse(data $(, $ret)?)
+    }
+
+    pub fn ifft_pairs_mixed_radix_dyn(data: &mut [($t, $t)] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        assert!(crate::mixed_radix::is_supported(data.len()));
+        crate::mixed_radix::digit_reverse_reorder_dyn(data);
+        compute_pairs_mixed_radix_inverse_dyn(data $(, $ret)?)
+    }
+
+    pub $($qualifier)? fn ifft_arrays<const N: usize>(data_re: &mut [$t; N], data_im: &mut [$t; N] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(N.is_power_of_two());
+        super::bit_reverse_reorder(data_re);
+        super::bit_reverse_reorder(data_im);
+        compute_arrays_inverse(data_re, data_im $(, $ret)?)
+    }
+
+    pub $($qualifier)? fn ifft_arrays_dyn(data_re: &mut [$t], data_im: &mut [$t] $(, $ret: $ret_type)?) $(-> $ret_type)? {
+        debug_assert!(data_re.len().is_power_of_two());
+        debug_assert!(data_re.len() == data_im.len());
+        super::bit_reverse_reorder_dyn(data_re);
+        super::bit_reverse_reorder_dyn(data_im);
+        compute_arrays_inverse_dyn(data_re, data_im $(, $ret)?)
+    }
+
     } };
 }
 
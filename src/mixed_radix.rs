@@ -0,0 +1,218 @@
+// Mixed-radix decimation in time fft for sizes of the form 2^a * 3^b * 5^c.
+// The stages use radix 4 while possible, then radix 2, 3 and 5 in this order.
+// The input is reordered with a digit reversal matching this sequence of radices.
+
+pub(crate) const fn is_supported(mut len: usize) -> bool {
+    if len == 0 {
+        return false;
+    }
+    while len.is_multiple_of(2) { len /= 2; }
+    while len.is_multiple_of(3) { len /= 3; }
+    while len.is_multiple_of(5) { len /= 5; }
+    len == 1
+}
+
+// radix of the next stage, `len` is the remaining size
+pub(crate) const fn next_radix(len: usize) -> usize {
+    if len.is_multiple_of(4) { 4 }
+    else if len.is_multiple_of(2) { 2 }
+    else if len.is_multiple_of(3) { 3 }
+    else { 5 }
+}
+
+// index of the sample which has to be at `pos` before the first stage
+const fn digit_reverse(mut pos: usize, len: usize) -> usize {
+    let mut rest = len;
+    let mut rev = 0;
+    while rest > 1 {
+        let radix = next_radix(rest);
+        rest /= radix;
+        rev += (pos % radix) * rest;
+        pos /= radix;
+    }
+    rev
+}
+
+pub(crate) fn digit_reverse_reorder_dyn<T>(data: &mut [T]) {
+    let len = data.len();
+    crate::reorder_dyn(data, |pos| digit_reverse(pos, len));
+}
+
+macro_rules! mixed_radix_impl {
+    // Butterflies of a single stage. `w3` and `w5` are the forward twiddle
+    // factors e^(-2 pi i / 3) and (e^(-2 pi i / 5), e^(-4 pi i / 5)).
+    (butterfly; $t:ty; $div2:expr) => {
+        fn mixed_radix_butterfly(v: &mut [($t, $t); 5], radix: usize, inverse: bool, w3: Twiddle, w5: (Twiddle, Twiddle)) {
+            // multiplication by -i, or by i for the inverse transform
+            let rot = |x: ($t, $t)| if inverse { (-x.1, x.0) } else { (x.1, -x.0) };
+            let add = |a: ($t, $t), b: ($t, $t)| (a.0 + b.0, a.1 + b.1);
+            let sub = |a: ($t, $t), b: ($t, $t)| (a.0 - b.0, a.1 - b.1);
+
+            match radix {
+                2 => {
+                    let (a, b) = (v[0], v[1]);
+                    v[0] = add(a, b);
+                    v[1] = sub(a, b);
+                }
+                3 => {
+                    let (sum, diff) = (add(v[1], v[2]), sub(v[1], v[2]));
+                    let mid = (v[0].0 - $div2(sum.0), v[0].1 - $div2(sum.1));
+                    let side = rot(mul_real(diff, -w3.1));
+                    v[0] = add(v[0], sum);
+                    v[1] = add(mid, side);
+                    v[2] = sub(mid, side);
+                }
+                4 => {
+                    let (a, b) = (add(v[0], v[2]), sub(v[0], v[2]));
+                    let (c, d) = (add(v[1], v[3]), rot(sub(v[1], v[3])));
+                    v[0] = add(a, c);
+                    v[1] = add(b, d);
+                    v[2] = sub(a, c);
+                    v[3] = sub(b, d);
+                }
+                _ => {
+                    let (cos1, sin1, cos2, sin2) = (w5.0.0, -w5.0.1, w5.1.0, -w5.1.1);
+                    let (sum1, sum2) = (add(v[1], v[4]), add(v[2], v[3]));
+                    let (diff1, diff2) = (sub(v[1], v[4]), sub(v[2], v[3]));
+                    let a1 = add(v[0], add(mul_real(sum1, cos1), mul_real(sum2, cos2)));
+                    let a2 = add(v[0], add(mul_real(sum1, cos2), mul_real(sum2, cos1)));
+                    let b1 = rot(add(mul_real(diff1, sin1), mul_real(diff2, sin2)));
+                    let b2 = rot(sub(mul_real(diff1, sin2), mul_real(diff2, sin1)));
+                    v[0] = add(v[0], add(sum1, sum2));
+                    v[1] = add(a1, b1);
+                    v[2] = add(a2, b2);
+                    v[3] = sub(a2, b2);
+                    v[4] = sub(a1, b1);
+                }
+            }
+        }
+    };
+
+    (
+        float; $t:ty; $len:expr; $inverse:literal;
+        ($($arg:ident: $arg_type:ty),*);
+        $data:ident; $x:ident; $x_re:expr; $x_im:expr;
+        fn $($signature:tt)*
+    ) => {
+        mixed_radix_impl!(
+            $len, $inverse,
+            headroom: |_limit| {},
+            finish: {
+                // scale by 1/N so that a forward and inverse transform cancel out
+                if $inverse {
+                    let scale = 1. / ($len as $t);
+                    let mut $x = 0;
+                    while $x < $len {
+                        $x_re *= scale;
+                        $x_im *= scale;
+                        $x += 1;
+                    }
+                }
+            },
+            ($($arg: $arg_type),*);
+            $x; $x_re; $x_im;
+            fn $($signature)*
+        );
+    };
+
+    (
+        int; $t:ty; $wide:ty; $len:expr; $inverse:literal;
+        ($($arg:ident: $arg_type:ty),*) $(-> $ret:ident: $ret_type:ty)?;
+        $data:ident; $x:ident; $x_re:expr; $x_im:expr;
+        $($signature:tt)*
+    ) => {
+        mixed_radix_impl!(
+            $len, $inverse,
+            headroom: |limit| {
+                $($ret += limit_dyn($data, limit) as $ret_type;)?
+            },
+            finish: {
+                // 1/N = 2^-log2 * (2^log2 / N), the exponent takes the power of two
+                // and the values are scaled by the remaining factor in (0.5, 1]
+                if $inverse {
+                    let log2 = usize::BITS - 1 - ($len).leading_zeros();
+                    $($ret -= log2 as $ret_type;)?
+                    if !($len).is_power_of_two() {
+                        let scale = ratio(1 << log2, $len as u64);
+                        let mut $x = 0;
+                        while $x < $len {
+                            let y = mul_real(($x_re, $x_im), scale);
+                            $x_re = y.0;
+                            $x_im = y.1;
+                            $x += 1;
+                        }
+                    }
+                }
+            },
+            ($($arg: $arg_type),*) $(-> $ret: $ret_type)?;
+            $x; $x_re; $x_im;
+            $($signature)*
+        );
+    };
+
+    (
+        $len:expr, $inverse:literal,
+        headroom: |$limit:ident| $headroom:block,
+        finish: $finish:block,
+        ($($arg:ident: $arg_type:ty),*) $(-> $ret:ident: $ret_type:ty)?;
+        $x:ident; $x_re:expr; $x_im:expr;
+        $($signature:tt)*
+    ) => {
+
+    $($signature)* ($($arg: $arg_type),* $(, mut $ret: $ret_type)?) $(-> $ret_type)? {
+        let w3 = twiddle_ratio(1, 3);
+        let w5 = (twiddle_ratio(1, 5), twiddle_ratio(2, 5));
+
+        let mut sub = 1;
+        while sub < $len {
+            let radix = crate::mixed_radix::next_radix($len / sub);
+            // after the twiddle factors a radix r butterfly can grow a component
+            // 1 + (r - 1) sqrt(2) times, the limits are the inverses of that
+            {
+                let $limit = match radix { 2 => 27131, 3 => 17090, 4 => 12480, _ => 9820 };
+                $headroom
+            }
+            let span = sub * radix;
+            let mut k = 0;
+            while k < sub {
+                // w[q] = W^(q * k), with W = e^(-2 pi i / span)
+                let mut w = [twiddle(0); 5];
+                let mut q = 1;
+                while q < radix {
+                    let t = twiddle_ratio(q * k, span);
+                    // the inverse transform uses conjugated twiddle factors
+                    w[q] = if $inverse { (t.0, -t.1) } else { t };
+                    q += 1;
+                }
+
+                let mut base = k;
+                while base < $len {
+                    let mut v = [{ let $x = base; ($x_re, $x_im) }; 5];
+                    let mut q = 1;
+                    while q < radix {
+                        let $x = base + q * sub;
+                        let y = ($x_re, $x_im);
+                        // the first twiddle factors are 1, skip the multiplication
+                        v[q] = if k == 0 { y } else { mul(y, w[q]) };
+                        q += 1;
+                    }
+                    mixed_radix_butterfly(&mut v, radix, $inverse, w3, w5);
+                    let mut q = 0;
+                    while q < radix {
+                        let $x = base + q * sub;
+                        $x_re = v[q].0;
+                        $x_im = v[q].1;
+                        q += 1;
+                    }
+                    base += span;
+                }
+                k += 1;
+            }
+            sub = span;
+        }
+        $finish
+        $($ret)?
+    }
+
+    };
+}
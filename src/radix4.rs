@@ -0,0 +1,148 @@
+// Radix-4 decimation in time fft. Takes the input in the same (radix-2) bit
+// reversed order as fft_impl, two radix-2 stages are merged into a single pass
+// which needs 3 instead of 4 complex multiplications per 4 points.
+// If log2(N) is odd, a single radix-2 pass is performed first.
+macro_rules! radix4_impl {
+    (
+        float; $t:ty; $len:expr; $inverse:literal;
+        ($($arg:ident: $arg_type:ty),*);
+        $data:ident; $x:ident; $x_re:expr; $x_im:expr;
+        fn $($signature:tt)*
+    ) => {
+        radix4_impl!(
+            $len, $inverse,
+            headroom: |_limit| {},
+            finish: {
+                // scale by 1/N so that a forward and inverse transform cancel out
+                if $inverse {
+                    let scale = 1. / ($len as $t);
+                    let mut $x = 0;
+                    while $x < $len {
+                        $x_re *= scale;
+                        $x_im *= scale;
+                        $x += 1;
+                    }
+                }
+            },
+            ($($arg: $arg_type),*);
+            $x; $x_re; $x_im;
+            fn $($signature)*
+        );
+    };
+
+    (
+        int; $t:ty; $wide:ty; $len:expr; $inverse:literal;
+        ($($arg:ident: $arg_type:ty),*) $(-> $ret:ident: $ret_type:ty)?;
+        $data:ident; $x:ident; $x_re:expr; $x_im:expr;
+        $($signature:tt)*
+    ) => {
+        radix4_impl!(
+            $len, $inverse,
+            // with the twiddle factors a radix-4 butterfly can grow a component
+            // 1 + 3 sqrt(2) times, so the values of a pass are halved until they
+            // are below 2^(BITS - 1) / (1 + 3 sqrt(2)), 12480 / 2^16 of it. The
+            // leading radix-2 pass has no twiddle factors, it only needs half.
+            headroom: |limit| {
+                $($ret += limit_dyn($data, limit) as $ret_type;)?
+            },
+            finish: {
+                // the 1/N factor of the inverse transform only affects the exponent
+                if $inverse {
+                    $($ret -= ($len).trailing_zeros() as $ret_type;)?
+                }
+            },
+            ($($arg: $arg_type),*) $(-> $ret: $ret_type)?;
+            $x; $x_re; $x_im;
+            $($signature)*
+        );
+    };
+
+    (
+        $len:expr, $inverse:literal,
+        headroom: |$limit:ident| $headroom:block,
+        finish: $finish:block,
+        ($($arg:ident: $arg_type:ty),*) $(-> $ret:ident: $ret_type:ty)?;
+        $x:ident; $x_re:expr; $x_im:expr;
+        $($signature:tt)*
+    ) => {
+
+    $($signature)* ($($arg: $arg_type),* $(, mut $ret: $ret_type)?) $(-> $ret_type)? {
+        let mut step = 1;
+
+        if ($len).trailing_zeros() & 1 == 1 {
+            { let $limit = 1 << 15; $headroom }
+            let mut i = 0;
+            while i < $len {
+                let $x = i;
+                let a = ($x_re, $x_im);
+                let $x = i + 1;
+                let b = ($x_re, $x_im);
+                let $x = i;
+                $x_re = a.0 + b.0;
+                $x_im = a.1 + b.1;
+                let $x = i + 1;
+                $x_re = a.0 - b.0;
+                $x_im = a.1 - b.1;
+                i += 2;
+            }
+            step = 2;
+        }
+
+        while step < $len {
+            { let $limit = 12480; $headroom }
+            let jump = step << 2;
+            let mut group = 0;
+            while group < step {
+                // w1 = W^group, w2 = W^(2 * group), w3 = W^(3 * group), with W = e^(-2 pi i / (4 * step))
+                let angle = (group as crate::Angle) << (crate::Angle::BITS - 1 - step.trailing_zeros());
+                let (angle3, past_pi) = angle.overflowing_mul(3);
+                let (w1, w2, w3) = (twiddle(angle), twiddle(angle << 1), twiddle(angle3));
+                let w3 = if past_pi { (-w3.0, -w3.1) } else { w3 };
+                // the inverse transform uses conjugated twiddle factors
+                let (w1, w2, w3) = if $inverse {
+                    ((w1.0, -w1.1), (w2.0, -w2.1), (w3.0, -w3.1))
+                }
+                else {
+                    (w1, w2, w3)
+                };
+
+                let mut i = group;
+                while i < $len {
+                    let $x = i;
+                    let t0 = ($x_re, $x_im);
+                    let $x = i + step;
+                    let t1 = mul(($x_re, $x_im), w2);
+                    let $x = i + 2 * step;
+                    let t2 = mul(($x_re, $x_im), w1);
+                    let $x = i + 3 * step;
+                    let t3 = mul(($x_re, $x_im), w3);
+
+                    let (a, b) = ((t0.0 + t1.0, t0.1 + t1.1), (t0.0 - t1.0, t0.1 - t1.1));
+                    let (c, d) = ((t2.0 + t3.0, t2.1 + t3.1), (t2.0 - t3.0, t2.1 - t3.1));
+                    // d * -i, or d * i for the inverse transform
+                    let d = if $inverse { (-d.1, d.0) } else { (d.1, -d.0) };
+
+                    let $x = i;
+                    $x_re = a.0 + c.0;
+                    $x_im = a.1 + c.1;
+                    let $x = i + step;
+                    $x_re = b.0 + d.0;
+                    $x_im = b.1 + d.1;
+                    let $x = i + 2 * step;
+                    $x_re = a.0 - c.0;
+                    $x_im = a.1 - c.1;
+                    let $x = i + 3 * step;
+                    $x_re = b.0 - d.0;
+                    $x_im = b.1 - d.1;
+                    i += jump;
+                }
+                group += 1;
+            }
+            step = jump;
+        }
+        $finish
+        $($ret)?
+    }
+
+    };
+}
@@ -0,0 +1,122 @@
+// Sliding dft, keeps selected bins of the dft of the last N samples up to date
+// with constant work per bin and sample (Jacobsen, Lyons 2003):
+// X[k] <- r * e^(2 pi i k / N) * (X[k] + x[n] - r^N * x[n - N])
+// The rounding errors of the twiddle factors would accumulate forever, the
+// damping factor r < 1 makes them decay instead. Sample m of the window, counted
+// from the oldest, is then weighted by r^(N - m).
+macro_rules! sliding_dft_impl {
+    (float; $t:ty;) => {
+        const SLIDING_MAX_DAMPING: $t = 1.;
+
+        fn sliding_input(x: $t, _len: usize) -> $t {
+            x
+        }
+
+        fn sliding_mul(a: $t, b: $t) -> $t {
+            a * b
+        }
+
+        fn sliding_rotate(x: ($t, $t), w: Twiddle) -> ($t, $t) {
+            mul(x, w)
+        }
+
+        sliding_dft_impl!($t;);
+    };
+
+    (int; $t:ty; $wide:ty; $ret_init:literal: $ret_type:ty) => {
+        // The twiddle factors are accurate to about 2^-16, an error the damping
+        // also amplifies 1 / (1 - r) times. r <= 1 - 2^-8 keeps it below 1%.
+        const SLIDING_MAX_DAMPING: $t = <$t>::MAX - (<$t>::MAX >> 8);
+
+        // the samples are scaled by 2^-shift, 2^shift >= 2N keeps the bins from overflowing
+        const fn sliding_shift(len: usize) -> u32 {
+            usize::BITS - (2 * len - 1).leading_zeros()
+        }
+
+        fn sliding_input(x: $t, len: usize) -> $t {
+            x >> sliding_shift(len).min(<$t>::BITS - 1)
+        }
+
+        // Product of two fixed point numbers in (-1, 1). The products are rounded to
+        // nearest, the damping would amplify a truncation bias 1 / (1 - r) times.
+        fn sliding_mul(a: $t, b: $t) -> $t {
+            let shift = <$t>::BITS - 1;
+            ((a as $wide * b as $wide + (1 << (shift - 1))) >> shift) as $t
+        }
+
+        // mul rounded to nearest
+        fn sliding_rotate(x: ($t, $t), w: Twiddle) -> ($t, $t) {
+            let round = 1 << (MUL_SHIFT - 1);
+            (
+                (((x.0 as $wide * w.0) - (x.1 as $wide * w.1) + round) >> MUL_SHIFT) as $t,
+                (((x.0 as $wide * w.1) + (x.1 as $wide * w.0) + round) >> MUL_SHIFT) as $t,
+            )
+        }
+
+        sliding_dft_impl!($t; $ret_init: $ret_type);
+    };
+
+    ($t:ty; $($ret_init:literal: $ret_type:ty)?) => {
+
+    pub struct SlidingDft<const N: usize, const B: usize> {
+        history: [$t; N],
+        pos: usize,
+        bins: [($t, $t); B],
+        rotations: [Twiddle; B],
+        damping: $t,
+        // r^N, the weight of the sample leaving the window
+        decay: $t,
+    }
+
+    impl<const N: usize, const B: usize> SlidingDft<N, B> {
+        // Tracks the bins k = bins[0], bins[1], ... of the N point dft, starting from
+        // a window of zeros. `damping` is r in the sample format, e.g. 0.999 or
+        // 32512 for i16. The integer variants accept at most 1 - 2^-8, 32640 for
+        // i16, the floating point variants can use exactly 1.
+        pub fn new(bins: [usize; B], damping: $t) -> Self {
+            debug_assert!(N > 0);
+            debug_assert!(damping <= SLIDING_MAX_DAMPING);
+            // r^N, by squaring from the highest bit of N down
+            let mut decay = damping;
+            for bit in (0..usize::BITS - 1 - N.leading_zeros()).rev() {
+                decay = sliding_mul(decay, decay);
+                if (N >> bit) & 1 != 0 {
+                    decay = sliding_mul(decay, damping);
+                }
+            }
+            SlidingDft {
+                history: [0 as $t; N],
+                pos: 0,
+                bins: [(0 as $t, 0 as $t); B],
+                // e^(2 pi i k / N)
+                rotations: bins.map(|k| {
+                    let w = twiddle_ratio(k, N);
+                    (w.0, -w.1)
+                }),
+                damping,
+                decay,
+            }
+        }
+
+        // Adds the sample x[n] and drops x[n - N]. The integer variants return
+        // the exponent of the bins, which only depends on N.
+        pub fn update(&mut self, x: $t) $(-> $ret_type)? {
+            let x = sliding_input(x, N);
+            let old = sliding_mul(self.history[self.pos], self.decay);
+            self.history[self.pos] = x;
+            self.pos = if self.pos + 1 == N { 0 } else { self.pos + 1 };
+
+            for (bin, w) in self.bins.iter_mut().zip(self.rotations.iter()) {
+                let z = sliding_rotate((bin.0 + x - old, bin.1), *w);
+                *bin = (sliding_mul(z.0, self.damping), sliding_mul(z.1, self.damping));
+            }
+            $($ret_init + sliding_shift(N) as $ret_type)?
+        }
+
+        pub fn bins(&self) -> &[($t, $t); B] {
+            &self.bins
+        }
+    }
+
+    };
+}
@@ -0,0 +1,156 @@
+// Split-radix decimation in frequency fft (Sorensen, Heideman, Burrus 1986).
+// Takes the input in natural order and leaves the output in bit reversed order.
+// Each pass splits a block into one half and two quarters with an L-shaped
+// butterfly, so only the quarters get multiplied by twiddle factors.
+// Because a pass doesn't touch every value, the integer variants
+// rescale the whole buffer before a pass when needed.
+macro_rules! split_radix_impl {
+    (
+        float; $t:ty; $len:expr; $inverse:literal;
+        ($($arg:ident: $arg_type:ty),*);
+        $data:ident; $x:ident; $x_re:expr; $x_im:expr;
+        fn $($signature:tt)*
+    ) => {
+        split_radix_impl!(
+            $len, $inverse,
+            headroom: |_limit| {},
+            finish: {
+                // scale by 1/N so that a forward and inverse transform cancel out
+                if $inverse {
+                    let scale = 1. / ($len as $t);
+                    let mut $x = 0;
+                    while $x < $len {
+                        $x_re *= scale;
+                        $x_im *= scale;
+                        $x += 1;
+                    }
+                }
+            },
+            ($($arg: $arg_type),*);
+            $x; $x_re; $x_im;
+            fn $($signature)*
+        );
+    };
+
+    (
+        int; $t:ty; $wide:ty; $len:expr; $inverse:literal;
+        ($($arg:ident: $arg_type:ty),*) $(-> $ret:ident: $ret_type:ty)?;
+        $data:ident; $x:ident; $x_re:expr; $x_im:expr;
+        $($signature:tt)*
+    ) => {
+        split_radix_impl!(
+            $len, $inverse,
+            headroom: |limit| {
+                $($ret += limit_dyn($data, limit) as $ret_type;)?
+            },
+            finish: {
+                // the 1/N factor of the inverse transform only affects the exponent
+                if $inverse {
+                    $($ret -= ($len).trailing_zeros() as $ret_type;)?
+                }
+            },
+            ($($arg: $arg_type),*) $(-> $ret: $ret_type)?;
+            $x; $x_re; $x_im;
+            $($signature)*
+        );
+    };
+
+    (
+        $len:expr, $inverse:literal,
+        headroom: |$limit:ident| $headroom:block,
+        finish: $finish:block,
+        ($($arg:ident: $arg_type:ty),*) $(-> $ret:ident: $ret_type:ty)?;
+        $x:ident; $x_re:expr; $x_im:expr;
+        $($signature:tt)*
+    ) => {
+
+    $($signature)* ($($arg: $arg_type),* $(, mut $ret: $ret_type)?) $(-> $ret_type)? {
+        // L-shaped butterflies, the half can grow 2 times and a component of the
+        // quarters 4 times, or 4 sqrt(2) times after the twiddle factor, so the
+        // values have to stay below 2^(BITS - 1) / (4 sqrt(2)), 11560 / 2^16 of it
+        let mut size = $len;
+        while size > 2 {
+            { let $limit = 11560; $headroom }
+            let quarter = size >> 2;
+            let mut j = 0;
+            while j < quarter {
+                // w1 = W^j, w3 = W^(3 * j), with W = e^(-2 pi i / size)
+                let angle = (j as crate::Angle) << (crate::Angle::BITS + 1 - size.trailing_zeros());
+                let (angle3, past_pi) = angle.overflowing_mul(3);
+                let (w1, w3) = (twiddle(angle), twiddle(angle3));
+                let w3 = if past_pi { (-w3.0, -w3.1) } else { w3 };
+                // the inverse transform uses conjugated twiddle factors
+                let (w1, w3) = if $inverse { ((w1.0, -w1.1), (w3.0, -w3.1)) } else { (w1, w3) };
+
+                let mut start = j;
+                let mut jump = size << 1;
+                while start < $len {
+                    let mut i = start;
+                    while i < $len {
+                        let $x = i;
+                        let x0 = ($x_re, $x_im);
+                        let $x = i + quarter;
+                        let x1 = ($x_re, $x_im);
+                        let $x = i + 2 * quarter;
+                        let x2 = ($x_re, $x_im);
+                        let $x = i + 3 * quarter;
+                        let x3 = ($x_re, $x_im);
+
+                        let d02 = (x0.0 - x2.0, x0.1 - x2.1);
+                        let d13 = (x1.0 - x3.0, x1.1 - x3.1);
+                        // d13 * -i, or d13 * i for the inverse transform
+                        let d13 = if $inverse { (-d13.1, d13.0) } else { (d13.1, -d13.0) };
+                        let y2 = (d02.0 + d13.0, d02.1 + d13.1);
+                        let y3 = (d02.0 - d13.0, d02.1 - d13.1);
+                        // the first twiddle factors are 1, skip the multiplication
+                        let (y2, y3) = if j == 0 { (y2, y3) } else { (mul(y2, w1), mul(y3, w3)) };
+
+                        let $x = i;
+                        $x_re = x0.0 + x2.0;
+                        $x_im = x0.1 + x2.1;
+                        let $x = i + quarter;
+                        $x_re = x1.0 + x3.0;
+                        $x_im = x1.1 + x3.1;
+                        let $x = i + 2 * quarter;
+                        $x_re = y2.0;
+                        $x_im = y2.1;
+                        let $x = i + 3 * quarter;
+                        $x_re = y3.0;
+                        $x_im = y3.1;
+                        i += jump;
+                    }
+                    start = 2 * jump - size + j;
+                    jump <<= 2;
+                }
+                j += 1;
+            }
+            size >>= 1;
+        }
+
+        // remaining length 2 butterflies, which only grow 2 times
+        { let $limit = 1 << 15; $headroom }
+        let mut start = 0;
+        let mut jump = 4;
+        while start < $len {
+            let mut i = start;
+            while i < $len {
+                let $x = i;
+                let a = ($x_re, $x_im);
+                let $x = i + 1;
+                let b = ($x_re, $x_im);
+                $x_re = a.0 - b.0;
+                $x_im = a.1 - b.1;
+                let $x = i;
+                $x_re = a.0 + b.0;
+                $x_im = a.1 + b.1;
+                i += jump;
+            }
+            start = 2 * jump - 2;
+            jump <<= 2;
+        }
+        $finish
+        $($ret)?
+    }
+
+    };
+}
@@ -0,0 +1,122 @@
+// Short-time Fourier transform of a stream of real samples. Every HOP samples
+// the last 2N samples are multiplied by a window and transformed with rfft into
+// a half spectrum of N pairs. Istft transforms such spectra back and overlap-adds
+// them into a stream again.
+macro_rules! stft_impl {
+    ($t:ty; $($ret:ident = $ret_init:literal: $ret_type:ty)?) => {
+
+    pub struct Stft<'a, const N: usize, const HOP: usize> {
+        window: &'a [$t],
+        // the last 2N samples, the oldest one at `pos`
+        history: [($t, $t); N],
+        pos: usize,
+        // samples added since the last frame
+        count: usize,
+        frame: [($t, $t); N],
+    }
+
+    impl<'a, const N: usize, const HOP: usize> Stft<'a, N, HOP> {
+        // `window` holds the 2N weights applied to every frame. The samples before
+        // the start of the stream are taken as zeros, so the first frame is
+        // complete after HOP samples.
+        pub fn new(window: &'a [$t]) -> Self {
+            debug_assert!(N.is_power_of_two() && N >= 2);
+            debug_assert!(HOP > 0 && HOP <= 2 * N);
+            debug_assert!(window.len() == 2 * N);
+            Stft {
+                window,
+                history: [(0 as $t, 0 as $t); N],
+                pos: 0,
+                count: 0,
+                frame: [(0 as $t, 0 as $t); N],
+            }
+        }
+
+        // Adds any number of samples and calls `emit` with the half spectrum of
+        // every completed frame, in the layout of rfft. The integer variants also
+        // pass its exponent.
+        pub fn process(&mut self, samples: &[$t], mut emit: impl FnMut(&mut [($t, $t); N] $(, $ret_type)?)) {
+            for &x in samples {
+                *crate::dct::real_mut(&mut self.history, self.pos) = x;
+                self.pos = if self.pos + 1 == 2 * N { 0 } else { self.pos + 1 };
+                self.count += 1;
+                if self.count < HOP {
+                    continue;
+                }
+                self.count = 0;
+
+                for (i, &w) in self.window.iter().enumerate() {
+                    let x = *crate::dct::real_mut(&mut self.history, (self.pos + i) % (2 * N));
+                    *crate::dct::real_mut(&mut self.frame, i) = mul_samples((x, 0 as $t), (w, 0 as $t)).0;
+                }
+                $(let $ret: $ret_type =)? rfft(&mut self.frame);
+                emit(&mut self.frame $(, $ret)?);
+            }
+        }
+    }
+
+    pub struct Istft<'a, const N: usize, const HOP: usize> {
+        window: &'a [$t],
+        // the overlapping frames added together, the next output starts at 0
+        sum: [($t, $t); N],
+        $(sum_exp: $ret_type,)?
+    }
+
+    impl<'a, const N: usize, const HOP: usize> Istft<'a, N, HOP> {
+        // `window` holds the 2N weights applied to every transformed frame. The
+        // input of Stft is reconstructed when the products of both windows add up
+        // to 1 over the overlapping frames, sum analysis[n + j * HOP] *
+        // synthesis[n + j * HOP] = 1, e.g. square roots of a hann window for both
+        // and HOP = N.
+        pub fn new(window: &'a [$t]) -> Self {
+            debug_assert!(N.is_power_of_two() && N >= 2);
+            debug_assert!(HOP > 0 && HOP <= 2 * N);
+            debug_assert!(window.len() == 2 * N);
+            Istft {
+                window,
+                sum: [(0 as $t, 0 as $t); N],
+                $(sum_exp: $ret_init,)?
+            }
+        }
+
+        // Adds the frame with the half spectrum `spectrum`, which is overwritten,
+        // and writes the next HOP samples to `out`. They lag 2N - HOP samples behind
+        // the input of Stft. The integer variants take the exponent of the spectrum
+        // and return the exponent of the output, which changes from call to call.
+        pub fn process(&mut self, spectrum: &mut [($t, $t); N] $(, $ret: $ret_type)?, out: &mut [$t; HOP]) $(-> $ret_type)? {
+            $(let $ret: $ret_type =)? irfft(spectrum $(, $ret)?);
+            for (i, &w) in self.window.iter().enumerate() {
+                let x = crate::dct::real_mut(spectrum, i);
+                *x = mul_samples((*x, 0 as $t), (w, 0 as $t)).0;
+            }
+
+            $(
+            // the frame and the sum are brought to a common exponent
+            // with a redundant sign bit
+            let $ret: $ret_type = {
+                let frame_bits = redundant_bits(spectrum.iter().flat_map(|x| [x.0, x.1]));
+                let sum_bits = redundant_bits(self.sum.iter().flat_map(|x| [x.0, x.1]));
+                let exp = ($ret + 1 - frame_bits as $ret_type).max(self.sum_exp + 1 - sum_bits as $ret_type);
+                rescale(spectrum.iter_mut().flat_map(|x| [&mut x.0, &mut x.1]), exp - $ret);
+                rescale(self.sum.iter_mut().flat_map(|x| [&mut x.0, &mut x.1]), exp - self.sum_exp);
+                self.sum_exp = exp;
+                exp
+            };
+            )?
+
+            for (s, x) in self.sum.iter_mut().zip(spectrum.iter()) {
+                *s = (s.0 + x.0, s.1 + x.1);
+            }
+            for (i, y) in out.iter_mut().enumerate() {
+                *y = *crate::dct::real_mut(&mut self.sum, i);
+            }
+            for i in 0..2 * N {
+                let next = if i + HOP < 2 * N { *crate::dct::real_mut(&mut self.sum, i + HOP) } else { 0 as $t };
+                *crate::dct::real_mut(&mut self.sum, i) = next;
+            }
+            $($ret)?
+        }
+    }
+
+    };
+}
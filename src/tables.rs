@@ -5,6 +5,7 @@ pub type TrigTableType = i32;
 #[cfg(not(feature = "wide_trig_lut"))]
 pub type TrigTableType = i16;
 
+#[allow(clippy::large_const_arrays)]
 pub const TRIG_TABLE: [(TrigTableType, TrigTableType); (1 << TRIG_TABLE_BITS) + 1] = {
     type FullTrigTableType = i32;
     const FULL_TRIG_TABLE_BITS: u32 = 16;
@@ -0,0 +1,63 @@
+// Fast Walsh-Hadamard transform, the radix-2 fft loop with all the twiddle
+// factors equal to 1. Input and output are real and in natural order.
+macro_rules! wht_impl {
+    (
+        float; $t:ty; $len:expr;
+        ($($arg:ident: $arg_type:ty),*);
+        $x:ident; $x_val:expr;
+        $y:ident; $y_val:expr;
+        fn $($signature:tt)*
+    ) => {
+        fft_impl!(
+            $len, false,
+            loop_init: {},
+            multiply: {
+                let (a, b) = ($x_val, $y_val);
+                $x_val = a + b;
+                $y_val = a - b;
+            },
+            next_twiddle: |_angle| {},
+            finish: {},
+            ($($arg: $arg_type),*);
+            $x; $x_val; $x_val;
+            $y; $y_val; $y_val;
+            fn $($signature)*
+        );
+    };
+
+    (
+        int; $t:ty; $len:expr;
+        ($($arg:ident: $arg_type:ty),*) $(-> $ret:ident: $ret_type:ty)?;
+        $x:ident; $x_val:expr;
+        $y:ident; $y_val:expr;
+        $($signature:tt)*
+    ) => {
+        fft_impl!(
+            $len, false,
+            // same check as the integer fft, halve the stage if any value
+            // could overflow when doubled
+            loop_init: let scale = {
+                let mut scale: $t = 0;
+                let mut $x = 0;
+                while $x < $len {
+                    scale |= $x_val ^ ($x_val << 1);
+                    $x += 1;
+                }
+                let scale = ((scale >> (1 as $t).count_zeros()) & 1) as u32;
+                $($ret += scale as $ret_type;)?
+                scale
+            },
+            multiply: {
+                let (a, b) = ($x_val >> scale, $y_val >> scale);
+                $x_val = a + b;
+                $y_val = a - b;
+            },
+            next_twiddle: |_angle| {},
+            finish: {},
+            ($($arg: $arg_type),*) $(-> $ret: $ret_type)?;
+            $x; $x_val; $x_val;
+            $y; $y_val; $y_val;
+            $($signature)*
+        );
+    };
+}
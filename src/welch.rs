@@ -0,0 +1,220 @@
+// Welch's method, the power spectral density averaged over overlapping windowed
+// segments of L = window.len() samples. Pairs of segments are transformed
+// together with rfft_pairs_twice and |X[k]|^2 of the L / 2 + 1 one-sided bins is
+// accumulated. The result is scaled by 1 / (fs * sum w[n]^2), which corrects for
+// the equivalent noise bandwidth of the window and gives the density in
+// units^2 / Hz. All bins except the first and the last are doubled to fold in
+// the negative frequencies.
+macro_rules! welch_impl {
+    (float; $t:ty;) => {
+        // adds |X[k]|^2 of the first `halves` half spectra in `data`
+        fn accumulate_power(power: &mut [$t], data: &mut [($t, $t)], halves: usize) {
+            let len = power.len() - 1;
+            for half in data.chunks(len).take(halves) {
+                // (X[0], X[L / 2]) are both real
+                power[0] += half[0].0 * half[0].0;
+                power[len] += half[0].1 * half[0].1;
+                for k in 1..len {
+                    power[k] += half[k].0 * half[k].0 + half[k].1 * half[k].1;
+                }
+            }
+        }
+
+        fn power_density(power: &[$t], window: &[$t], segments: usize, sample_rate: $t, out: &mut [$t]) {
+            let energy: $t = window.iter().map(|w| w * w).sum();
+            let scale = 1. / (segments as $t * sample_rate * energy);
+            for (k, (y, p)) in out.iter_mut().zip(power.iter()).enumerate() {
+                let edge = k == 0 || k == power.len() - 1;
+                *y = if edge { p * scale } else { 2. * p * scale };
+            }
+        }
+
+        welch_impl!($t; $t; $t;);
+    };
+
+    (int; $t:ty; $wide:ty; $ret:ident = $ret_init:literal: $ret_type:ty) => {
+        // Adds |X[k]|^2 of the first `halves` half spectra in `data`, which have the
+        // exponent $ret. The squares are formed in $wide and brought to the exponent
+        // of the accumulated power, whichever of the two is larger.
+        fn accumulate_power(
+            power: &mut [$wide],
+            data: &mut [($t, $t)],
+            halves: usize,
+            power_exp: &mut $ret_type,
+            empty: bool,
+            $ret: $ret_type,
+        ) {
+            // with a redundant sign bit in the components a square is below 2^(W - 4)
+            let $ret = 2 * ($ret + normalize_dyn(data, 1));
+            if empty {
+                *power_exp = $ret;
+            }
+            // a sum below 2^(W - 2) leaves room for two more squares
+            let bits = power.iter().fold(0, |bits, p| bits | p);
+            if bits >> (<$wide>::BITS - 2) != 0 {
+                for p in power.iter_mut() {
+                    *p >>= 1;
+                }
+                *power_exp += 1;
+            }
+            if $ret > *power_exp {
+                let shift = (($ret - *power_exp) as u32).min(<$wide>::BITS - 1);
+                for p in power.iter_mut() {
+                    *p >>= shift;
+                }
+                *power_exp = $ret;
+            }
+            let shift = ((*power_exp - $ret) as u32).min(<$wide>::BITS - 1);
+
+            let square = |x: $t| x as $wide * x as $wide;
+            let len = power.len() - 1;
+            for half in data.chunks(len).take(halves) {
+                // (X[0], X[L / 2]) are both real
+                power[0] += square(half[0].0) >> shift;
+                power[len] += square(half[0].1) >> shift;
+                for k in 1..len {
+                    power[k] += (square(half[k].0) + square(half[k].1)) >> shift;
+                }
+            }
+        }
+
+        // The scale 1 / (segments * fs * sum w[n]^2) is split into a power of two,
+        // which goes to the exponent, and a factor in (0.5, 1].
+        fn power_density(
+            power: &[$wide],
+            window: &[$t],
+            segments: usize,
+            sample_rate: u32,
+            out: &mut [$t],
+            power_exp: $ret_type,
+        ) -> $ret_type {
+            let energy = window.iter().map(|&w| (w as i128 * w as i128) as u128).sum();
+            let (energy, energy_exp) = split_exponent(energy);
+            let (den, den_exp) = split_exponent(energy as u128 * sample_rate as u128 * segments as u128);
+            let scale = ratio(1 << 31, den);
+
+            // the doubled values have to fit as well
+            let bits = power.iter().fold(0, |bits, p| bits | p);
+            let shift = (<$wide>::BITS - bits.leading_zeros()).saturating_sub(<$t>::BITS - 2);
+            for (k, (y, &p)) in out.iter_mut().zip(power.iter()).enumerate() {
+                let edge = k == 0 || k == power.len() - 1;
+                let x = (p >> shift) as $t;
+                *y = mul_real((if edge { x } else { 2 * x }, 0), scale).0;
+            }
+            // sum w[n]^2 is counted in units of 2^-2(BITS - 1)
+            power_exp + shift as $ret_type + 2 * (<$t>::BITS - 1) as $ret_type
+                - 31 - (energy_exp + den_exp) as $ret_type
+        }
+
+        welch_impl!($t; $wide; u32; $ret = $ret_init: $ret_type);
+    };
+
+    ($t:ty; $acc:ty; $rate:ty; $($ret:ident = $ret_init:literal: $ret_type:ty)?) => {
+
+    pub struct Welch<'a> {
+        window: &'a [$t],
+        // the last L samples, the oldest one at `pos`
+        history: &'a mut [$t],
+        pos: usize,
+        // samples until the next segment is complete
+        next: usize,
+        hop: usize,
+        // two windowed segments, the first one waits for the second if `pending`
+        buffer: &'a mut [($t, $t)],
+        pending: bool,
+        power: &'a mut [$acc],
+        $(power_exp: $ret_type,)?
+        segments: usize,
+    }
+
+    impl<'a> Welch<'a> {
+        // Segments of L = window.len() samples, a power of two, start every
+        // L - overlap samples. `history` has to hold L values, `buffer` L pairs
+        // and `power` the L / 2 + 1 bins.
+        pub fn new(
+            window: &'a [$t],
+            overlap: usize,
+            history: &'a mut [$t],
+            buffer: &'a mut [($t, $t)],
+            power: &'a mut [$acc],
+        ) -> Self {
+            let len = window.len();
+            debug_assert!(len.is_power_of_two() && len >= 4);
+            debug_assert!(overlap < len);
+            debug_assert!(history.len() == len && buffer.len() == len && power.len() == len / 2 + 1);
+            history.fill(0 as $t);
+            power.fill(0 as $acc);
+            Welch {
+                window,
+                history,
+                pos: 0,
+                next: len,
+                hop: len - overlap,
+                buffer,
+                pending: false,
+                power,
+                $(power_exp: $ret_init,)?
+                segments: 0,
+            }
+        }
+
+        // Adds any number of samples, the power of every completed segment is
+        // accumulated.
+        pub fn process(&mut self, samples: &[$t]) {
+            let len = self.window.len();
+            for &x in samples {
+                self.history[self.pos] = x;
+                self.pos = if self.pos + 1 == len { 0 } else { self.pos + 1 };
+                self.next -= 1;
+                if self.next != 0 {
+                    continue;
+                }
+                self.next = self.hop;
+
+                let start = if self.pending { len } else { 0 };
+                for (i, &w) in self.window.iter().enumerate() {
+                    let x = self.history[(self.pos + i) % len];
+                    *crate::dct::real_mut(self.buffer, start + i) = mul_samples((x, 0 as $t), (w, 0 as $t)).0;
+                }
+                if self.pending {
+                    self.transform(2);
+                }
+                self.pending = !self.pending;
+            }
+        }
+
+        fn transform(&mut self, halves: usize) {
+            $(let $ret: $ret_type =)? rfft_pairs_twice_dyn(self.buffer);
+            accumulate_power(self.power, self.buffer, halves $(, &mut self.power_exp, self.segments == 0, $ret)?);
+            self.segments += halves;
+        }
+
+        // Number of segments averaged so far
+        pub fn segments(&self) -> usize {
+            self.segments
+        }
+
+        // Writes the one-sided power spectral density of the L / 2 + 1 bins to
+        // `out`, bin k is at k * fs / L Hz. A segment still waiting for its pair is
+        // transformed alone first. The integer variants take the sample rate in Hz
+        // and return the exponent of the density.
+        pub fn psd(&mut self, sample_rate: $rate, out: &mut [$t]) $(-> $ret_type)? {
+            let len = self.window.len();
+            debug_assert!(out.len() == len / 2 + 1);
+            if self.pending {
+                for i in len..2 * len {
+                    *crate::dct::real_mut(self.buffer, i) = 0 as $t;
+                }
+                self.transform(1);
+                self.pending = false;
+            }
+            if self.segments == 0 {
+                out.fill(0 as $t);
+                return $($ret_init as $ret_type)?;
+            }
+            power_density(self.power, self.window, self.segments, sample_rate, out $(, self.power_exp as $ret_type)?)
+        }
+    }
+
+    };
+}
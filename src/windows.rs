@@ -0,0 +1,160 @@
+// Periodic (DFT-even) window functions, w[n] for n in 0..len repeats with period
+// len, as used for spectral analysis. A symmetric window of M values is the
+// periodic window of M - 1 values followed by its first value again.
+// The coefficients are computed in fixed point, the cosines come from TRIG_TABLE.
+
+// fractional bits of the values returned by window_value
+pub(crate) const WINDOW_SHIFT: u32 = 48;
+
+// cosine sum coefficients in units of 10^-9, w[n] = a0 - a1 cos(t) + a2 cos(2t) - ...
+// with t = 2 pi n / len
+const HANN: [i64; 5] = [500_000_000, 500_000_000, 0, 0, 0];
+const HAMMING: [i64; 5] = [540_000_000, 460_000_000, 0, 0, 0];
+const BLACKMAN_HARRIS: [i64; 5] = [358_750_000, 488_290_000, 141_280_000, 11_680_000, 0];
+const FLAT_TOP: [i64; 5] = [215_578_950, 416_631_580, 277_263_158, 83_578_947, 6_947_368];
+
+// cos(2 pi * k / len) in units of 1 / TrigTableType::MAX
+const fn cos_ratio(k: usize, len: usize) -> i64 {
+    let turn = ((((k % len) as u64) << 32) / len as u64) as u32;
+    // the angle of sin_cos covers half a turn, cos(t + pi) = -cos(t)
+    let cos = crate::sin_cos(turn << 1).1 as i64;
+    if turn >> 31 != 0 { -cos } else { cos }
+}
+
+const fn cosine_sum(coeffs: &[i64; 5], n: usize, len: usize) -> i64 {
+    let mut sum = 0i128;
+    let mut j = 0;
+    while j < coeffs.len() {
+        let term = coeffs[j] as i128 * if j == 0 { crate::TrigTableType::MAX as i64 } else { cos_ratio(j * n, len) } as i128;
+        sum += if j & 1 == 0 { term } else { -term };
+        j += 1;
+    }
+    ((sum << WINDOW_SHIFT) / (1_000_000_000 * crate::TrigTableType::MAX as i128)) as i64
+}
+
+// I0(2 sqrt(q)) = sum q^k / (k!)^2, with q and the result in Q32
+const fn bessel_i0(q: u128) -> u128 {
+    let mut term = 1 << 32;
+    let mut sum = term;
+    let mut k = 1;
+    while term != 0 {
+        term = ((term * q) >> 32) / (k * k);
+        sum += term;
+        k += 1;
+    }
+    sum
+}
+
+// w[n] = I0(beta * sqrt(1 - x^2)) / I0(beta) with x = 2n / len - 1. The argument
+// of I0 is halved and squared, beta^2 * (1 - x^2) / 4 = beta^2 * n * (len - n) / len^2,
+// so no square root is needed.
+const fn kaiser(beta: u16, n: usize, len: usize) -> i64 {
+    // beta^2 has 16 fractional bits
+    let beta2 = beta as u128 * beta as u128;
+    let q = ((beta2 * (n * (len - n)) as u128) << 16) / (len as u128 * len as u128);
+    ((bessel_i0(q) << WINDOW_SHIFT) / bessel_i0(beta2 << 14)) as i64
+}
+
+// w[n] of the window of `len` values with WINDOW_SHIFT fractional bits
+pub(crate) const fn window_value(window: crate::Window, n: usize, len: usize) -> i64 {
+    match window {
+        crate::Window::Hann => cosine_sum(&HANN, n, len),
+        crate::Window::Hamming => cosine_sum(&HAMMING, n, len),
+        crate::Window::BlackmanHarris => cosine_sum(&BLACKMAN_HARRIS, n, len),
+        crate::Window::FlatTop => cosine_sum(&FLAT_TOP, n, len),
+        crate::Window::Kaiser(beta) => {
+            debug_assert!(beta <= 32 * 256);
+            kaiser(beta, n, len)
+        }
+    }
+}
+
+macro_rules! windows_impl {
+    (float; $t:ty; ; $($qualifier:tt)?) => {
+        const fn window_sample(w: i64) -> $t {
+            w as $t / (1u64 << crate::windows::WINDOW_SHIFT) as $t
+        }
+
+        // Mean of the window coefficients, the gain of a sinusoid centered on a bin
+        pub fn coherent_gain_dyn(window: &[$t]) -> $t {
+            window.iter().sum::<$t>() / window.len() as $t
+        }
+
+        // Equivalent noise bandwidth in bins, N * sum w[n]^2 / (sum w[n])^2
+        pub fn enbw_dyn(window: &[$t]) -> $t {
+            let sum: $t = window.iter().sum();
+            let energy: $t = window.iter().map(|w| w * w).sum();
+            window.len() as $t * energy / (sum * sum)
+        }
+
+        windows_impl!($t; $($qualifier)?);
+    };
+
+    (int; $t:ty; $ret:ident = $ret_init:literal: $ret_type:ty; $($qualifier:tt)?) => {
+        // rounded to Q(BITS - 1), a coefficient of 1 saturates
+        const fn window_sample(w: i64) -> $t {
+            let shift = crate::windows::WINDOW_SHIFT - (<$t>::BITS - 1);
+            let w = (w + (1 << (shift - 1))) >> shift;
+            if w > <$t>::MAX as i64 { <$t>::MAX } else { w as $t }
+        }
+
+        // Mean of the window coefficients in Q(BITS - 1), the gain of a sinusoid
+        // centered on a bin
+        pub fn coherent_gain_dyn(window: &[$t]) -> $t {
+            (window.iter().map(|&w| w as i128).sum::<i128>() / window.len() as i128) as $t
+        }
+
+        // Equivalent noise bandwidth in bins, N * sum w[n]^2 / (sum w[n])^2, as a
+        // value and its exponent
+        pub fn enbw_dyn(window: &[$t]) -> ($t, $ret_type) {
+            let sum = window.iter().map(|&w| w as i128).sum::<i128>().unsigned_abs();
+            let energy = window.iter().map(|&w| (w as i128 * w as i128) as u128).sum::<u128>();
+            let (num, num_exp) = split_exponent(window.len() as u128 * energy);
+            let (den, den_exp) = split_exponent(sum * sum);
+            // num / den is in (0.5, 2)
+            let value = (num << (<$t>::BITS - 2)) / den;
+            (value as $t, (num_exp - den_exp) as $ret_type - (<$t>::BITS - 2) as $ret_type)
+        }
+
+        windows_impl!($t; $($qualifier)?);
+    };
+
+    ($t:ty; $($qualifier:tt)?) => {
+
+    // Coefficients of the window of N values
+    pub $($qualifier)? fn window<const N: usize>(window: crate::Window) -> [$t; N] {
+        let mut out = [0 as $t; N];
+        let mut n = 0;
+        while n < N {
+            out[n] = window_sample(crate::windows::window_value(window, n, N));
+            n += 1;
+        }
+        out
+    }
+
+    pub fn window_dyn(window: crate::Window, out: &mut [$t]) {
+        let len = out.len();
+        for (n, w) in out.iter_mut().enumerate() {
+            *w = window_sample(crate::windows::window_value(window, n, len));
+        }
+    }
+
+    // Multiplies the complex samples by the window, one coefficient per pair
+    pub fn apply_window_dyn(data: &mut [($t, $t)], window: &[$t]) {
+        debug_assert!(data.len() == window.len());
+        for (x, &w) in data.iter_mut().zip(window.iter()) {
+            *x = mul_samples(*x, (w, 0 as $t));
+        }
+    }
+
+    // Multiplies the real samples stored as pairs, as taken by rfft, by the
+    // window of 2 * data.len() values
+    pub fn apply_window_real_dyn(data: &mut [($t, $t)], window: &[$t]) {
+        debug_assert!(2 * data.len() == window.len());
+        for (x, w) in data.iter_mut().zip(window.chunks_exact(2)) {
+            *x = (mul_samples((x.0, 0 as $t), (w[0], 0 as $t)).0, mul_samples((x.1, 0 as $t), (w[1], 0 as $t)).0);
+        }
+    }
+
+    };
+}
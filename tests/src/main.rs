@@ -5,13 +5,16 @@ extern crate rustfft;
 extern crate rand;
 extern crate nanofft;
 
-use rustfft::FftPlanner;
+use rustfft::{ FftPlanner, num_complex::Complex };
+use rand::{ Rng, thread_rng };
 
 trait Convert where Self: Sized {
     type RangeInfo;
 
     fn from_f64(x: f64) -> Self;
     fn into_f64(self, s: Self::RangeInfo) -> f64;
+    // range of a plain sample, such as the window coefficients
+    fn sample_range() -> Self::RangeInfo;
 }
 
 macro_rules! convert_float {
@@ -22,6 +25,7 @@ macro_rules! convert_float {
     
         fn from_f64(x: f64) -> Self { x as Self }
         fn into_f64(self, _: Self::RangeInfo) -> f64 { self as f64 }
+        fn sample_range() {}
     }
     
     )* };
@@ -38,6 +42,7 @@ macro_rules! convert_int {
             let scale = f64::from_bits(((i as i32 + 2 - f64::MIN_EXP) as u64) << (f64::MANTISSA_DIGITS - 1));
             self as f64 * scale
         }
+        fn sample_range() -> i16 { 1 - Self::BITS as i16 }
     }
     
     )* };
@@ -46,6 +51,371 @@ macro_rules! convert_int {
 convert_float!(f32 f64);
 convert_int!(i8 i16 i32 i64);
 
+// fft_pairs_batch_dyn, the integer variants share a single exponent
+trait Batch where Self: Convert {
+    fn fft_batch(data: &mut [(Self, Self)], channels: usize) -> Self::RangeInfo;
+}
+
+macro_rules! batch_float {
+    ($($t:ident)*) => { $(
+
+    impl Batch for $t {
+        fn fft_batch(data: &mut [($t, $t)], channels: usize) {
+            nanofft::$t::fft_pairs_batch_dyn(data, channels)
+        }
+    }
+
+    )* };
+}
+
+macro_rules! batch_int {
+    ($($t:ident)*) => { $(
+
+    impl Batch for $t {
+        fn fft_batch(data: &mut [($t, $t)], channels: usize) -> i16 {
+            let mut range = [0];
+            nanofft::$t::fft_pairs_batch_dyn(data, channels, &mut range);
+            range[0]
+        }
+    }
+
+    )* };
+}
+
+batch_float!(f32 f64);
+batch_int!(i16 i32);
+
+// channels of the batched transform
+const CHANNELS: usize = 4;
+
+// The streaming types, whose integer variants pass an exponent with every
+// call. The outputs are returned converted to f64.
+trait Streaming where Self: Convert {
+    fn overlap_add(kernel: &[Self], signal: &[Self]) -> Vec<f64>;
+    fn stft(window: &[Self], signal: &[Self]) -> Vec<f64>;
+    fn welch(window: &[Self], overlap: usize, signal: &[Self]) -> Vec<f64>;
+}
+
+macro_rules! streaming_float {
+    ($($t:ident)*) => { $(
+
+    impl Streaming for $t {
+        fn overlap_add(kernel: &[$t], signal: &[$t]) -> Vec<f64> {
+            let mut spectrum = [(0., 0.); CONVOLUTION_SPECTRUM];
+            nanofft::$t::convolution_kernel_dyn(kernel, &mut spectrum);
+            let (mut buffer, mut tail) = ([(0., 0.); 2 * CONVOLUTION_SPECTRUM], [0.; CONVOLUTION_TAIL]);
+            let mut filter = nanofft::$t::OverlapAdd::new(&spectrum, &mut buffer, &mut tail);
+            let mut out = signal.to_vec();
+            for block in out.chunks_mut(2 * (2 * CONVOLUTION_SPECTRUM - CONVOLUTION_TAIL)) {
+                filter.process(block);
+            }
+            out.iter().map(|&x| x as f64).collect()
+        }
+
+        fn stft(window: &[$t], signal: &[$t]) -> Vec<f64> {
+            let mut out = Vec::new();
+            nanofft::$t::Stft::<STFT_LEN, STFT_HOP>::new(window).process(signal, |frame| {
+                out.extend(frame.iter().flat_map(|x| [x.0 as f64, x.1 as f64]));
+            });
+            out
+        }
+
+        fn welch(window: &[$t], overlap: usize, signal: &[$t]) -> Vec<f64> {
+            let len = window.len();
+            let (mut history, mut buffer, mut power) = (vec![0.; len], vec![(0., 0.); len], vec![0.; len / 2 + 1]);
+            let mut welch = nanofft::$t::Welch::new(window, overlap, &mut history, &mut buffer, &mut power);
+            welch.process(signal);
+            let mut out = vec![0.; len / 2 + 1];
+            welch.psd(1., &mut out);
+            out.iter().map(|&x| x as f64).collect()
+        }
+    }
+
+    )* };
+}
+
+macro_rules! streaming_int {
+    ($($t:ident: $wide:ty)*) => { $(
+
+    impl Streaming for $t {
+        fn overlap_add(kernel: &[$t], signal: &[$t]) -> Vec<f64> {
+            let mut spectrum = [(0, 0); CONVOLUTION_SPECTRUM];
+            let spectrum_range = nanofft::$t::convolution_kernel_dyn(kernel, &mut spectrum);
+            let (mut buffer, mut tail) = ([(0, 0); 2 * CONVOLUTION_SPECTRUM], [0; CONVOLUTION_TAIL]);
+            let mut filter = nanofft::$t::OverlapAdd::new(&spectrum, spectrum_range, &mut buffer, &mut tail);
+            let mut out = Vec::new();
+            for block in signal.chunks(2 * (2 * CONVOLUTION_SPECTRUM - CONVOLUTION_TAIL)) {
+                let mut block = block.to_vec();
+                let range = filter.process(&mut block);
+                out.extend(block.iter().map(|x| x.into_f64(range)));
+            }
+            out
+        }
+
+        fn stft(window: &[$t], signal: &[$t]) -> Vec<f64> {
+            let mut out = Vec::new();
+            nanofft::$t::Stft::<STFT_LEN, STFT_HOP>::new(window).process(signal, |frame, range| {
+                out.extend(frame.iter().flat_map(|x| [x.0.into_f64(range), x.1.into_f64(range)]));
+            });
+            out
+        }
+
+        fn welch(window: &[$t], overlap: usize, signal: &[$t]) -> Vec<f64> {
+            let len = window.len();
+            let (mut history, mut buffer, mut power) = (vec![0; len], vec![(0, 0); len], vec![0 as $wide; len / 2 + 1]);
+            let mut welch = nanofft::$t::Welch::new(window, overlap, &mut history, &mut buffer, &mut power);
+            welch.process(signal);
+            let mut out = vec![0; len / 2 + 1];
+            let range = welch.psd(1, &mut out);
+            out.iter().map(|x| x.into_f64(range)).collect()
+        }
+    }
+
+    )* };
+}
+
+streaming_float!(f32 f64);
+streaming_int!(i16: i32 i32: i64);
+
+#[derive(Clone, Copy)]
+enum Kernel {
+    Radix2,
+    Radix4,
+    SplitRadix,
+    MixedRadix,
+    Bluestein,
+    Batch,
+}
+
+const KERNELS: [(&str, Kernel, usize); 10] = [
+    ("radix-2", Kernel::Radix2, 1024),
+    ("radix-4", Kernel::Radix4, 1024),
+    ("radix-4", Kernel::Radix4, 2048),
+    ("split radix", Kernel::SplitRadix, 1024),
+    ("split radix", Kernel::SplitRadix, 2048),
+    ("mixed radix", Kernel::MixedRadix, 960),
+    ("mixed radix", Kernel::MixedRadix, 1000),
+    ("bluestein", Kernel::Bluestein, 100),
+    ("bluestein", Kernel::Bluestein, 1009),
+    ("batch x4", Kernel::Batch, CHANNELS * 256),
+];
+
+// Random values, or a full scale tone at bin 1 of every `period` values, scaled
+// so that the larger component of each value is 1. The latter is the worst case
+// for the growth of the butterflies.
+fn kernel_input(len: usize, period: usize, full_scale: bool) -> Vec<Complex<f64>> {
+    let mut rng = thread_rng();
+    (0..len).map(|i| {
+        if full_scale {
+            let angle = 2. * std::f64::consts::PI * (i % period) as f64 / period as f64;
+            let (sin, cos) = angle.sin_cos();
+            Complex { re: cos, im: sin } / cos.abs().max(sin.abs())
+        }
+        else {
+            Complex { re: rng.gen::<f64>() * 2. - 1., im: rng.gen::<f64>() * 2. - 1. }
+        }
+    })
+    .collect()
+}
+
+#[derive(Clone, Copy)]
+enum Transform {
+    Dct2,
+    Mdct,
+    Hartley,
+    Walsh,
+    Goertzel,
+    SlidingDft,
+    OverlapAdd,
+    CrossCorrelation,
+    Autocorrelation,
+    Stft,
+    Welch,
+    Window,
+}
+
+const TRANSFORMS: [(&str, Transform, usize); 12] = [
+    ("dct-ii", Transform::Dct2, 1024),
+    ("mdct", Transform::Mdct, 1024),
+    ("hartley", Transform::Hartley, 1024),
+    ("walsh", Transform::Walsh, 1024),
+    ("goertzel", Transform::Goertzel, 4096),
+    ("sliding dft", Transform::SlidingDft, 1024),
+    ("overlap-add", Transform::OverlapAdd, 1152),
+    ("correlation", Transform::CrossCorrelation, 1024),
+    ("autocorr", Transform::Autocorrelation, 1024),
+    ("stft", Transform::Stft, 1024),
+    ("welch", Transform::Welch, 2048),
+    ("window", Transform::Window, 1024),
+];
+
+// low bins of a long input, where the coefficient of the recursion is close to 2
+const GOERTZEL_BINS: [usize; 5] = [0, 1, 2, 8, 2048];
+
+// window and bins of the sliding dft, which runs over 4 windows of samples
+const SLIDING_LEN: usize = 256;
+const SLIDING_BINS: [usize; 4] = [1, 10, 64, 128];
+const SLIDING_DAMPING: f64 = 1. - 1. / 256.;
+
+// frames of 128 samples, blocks of 2 * 96 samples and kernels of 33 taps
+const CONVOLUTION_SPECTRUM: usize = 64;
+const CONVOLUTION_TAIL: usize = 32;
+
+// frames of 2 * STFT_LEN samples
+const STFT_LEN: usize = 128;
+const STFT_HOP: usize = 64;
+
+// segments of 256 samples overlapping by half
+const WELCH_LEN: usize = 256;
+
+fn real_input(len: usize) -> Vec<f64> {
+    let mut rng = thread_rng();
+    (0..len).map(|_| rng.gen::<f64>() * 2. - 1.).collect()
+}
+
+// periodic hann window
+fn hann(len: usize) -> Vec<f64> {
+    (0..len).map(|n| 0.5 - 0.5 * (2. * std::f64::consts::PI * n as f64 / len as f64).cos()).collect()
+}
+
+// The references below compute the definitions given in the comments of
+// nanofft directly, in f64.
+
+fn dct2_reference(x: &[f64]) -> Vec<f64> {
+    let len = x.len();
+    (0..len).map(|k| {
+        (0..len).map(|n| x[n] * (std::f64::consts::PI * ((2 * n + 1) * k) as f64 / (2 * len) as f64).cos()).sum()
+    })
+    .collect()
+}
+
+fn mdct_reference(x: &[f64]) -> Vec<f64> {
+    let half = x.len() / 2;
+    (0..half).map(|k| {
+        (0..x.len()).map(|n| {
+            let angle = std::f64::consts::PI * (n as f64 + 0.5 + half as f64 / 2.) * (k as f64 + 0.5) / half as f64;
+            x[n] * angle.cos()
+        })
+        .sum()
+    })
+    .collect()
+}
+
+fn hartley_reference(x: &[f64]) -> Vec<f64> {
+    let len = x.len();
+    (0..len).map(|k| {
+        (0..len).map(|n| {
+            let angle = 2. * std::f64::consts::PI * ((n * k) % len) as f64 / len as f64;
+            x[n] * (angle.cos() + angle.sin())
+        })
+        .sum()
+    })
+    .collect()
+}
+
+// natural (Hadamard) order
+fn walsh_reference(x: &[f64]) -> Vec<f64> {
+    (0..x.len()).map(|k| {
+        (0..x.len()).map(|n| if (n & k).count_ones() & 1 == 0 { x[n] } else { -x[n] }).sum()
+    })
+    .collect()
+}
+
+// X[k] of the dft of the real samples
+fn dft_bin(x: &[f64], k: usize) -> Complex<f64> {
+    x.iter().enumerate().map(|(n, &value)| {
+        let angle = -2. * std::f64::consts::PI * ((n * k) % x.len()) as f64 / x.len() as f64;
+        Complex::from_polar(value, angle)
+    })
+    .sum()
+}
+
+fn goertzel_reference(x: &[f64]) -> Vec<f64> {
+    GOERTZEL_BINS.iter().map(|&k| dft_bin(x, k).norm_sqr()).collect()
+}
+
+// the bins over the last SLIDING_LEN samples, sample m of the window, counted
+// from the oldest, weighted by r^(N - m)
+fn sliding_reference(x: &[f64], damping: f64) -> Vec<f64> {
+    let window = &x[x.len() - SLIDING_LEN..];
+    SLIDING_BINS.iter().flat_map(|&k| {
+        let bin: Complex<f64> = window.iter().enumerate().map(|(m, &x)| {
+            let angle = -2. * std::f64::consts::PI * ((m * k) % SLIDING_LEN) as f64 / SLIDING_LEN as f64;
+            Complex::from_polar(x * damping.powi((SLIDING_LEN - m) as i32), angle)
+        })
+        .sum();
+        [bin.re, bin.im]
+    })
+    .collect()
+}
+
+fn convolution_reference(kernel: &[f64], x: &[f64]) -> Vec<f64> {
+    (0..x.len()).map(|n| kernel.iter().take(n + 1).enumerate().map(|(j, h)| h * x[n - j]).sum()).collect()
+}
+
+// r[m] = sum a[n] * b[n + m] over the circular lags of N = 2 * a.len() values
+fn correlation_reference(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let len = 2 * a.len();
+    (0..len).map(|m| a.iter().enumerate().filter_map(|(n, x)| b.get((n + m) % len).map(|y| x * y)).sum()).collect()
+}
+
+// biased estimate r[m] / N
+fn autocorrelation_reference(x: &[f64]) -> Vec<f64> {
+    let len = x.len();
+    (0..len).map(|m| (0..len - m).map(|n| x[n] * x[n + m]).sum::<f64>() / len as f64).collect()
+}
+
+// half spectra of the windowed last 2N samples every HOP samples, in the layout of rfft
+fn stft_reference(planner: &mut FftPlanner<f64>, window: &[f64], x: &[f64]) -> Vec<f64> {
+    let len = 2 * STFT_LEN;
+    let fft = planner.plan_fft_forward(len);
+    (1..=x.len() / STFT_HOP).flat_map(|frame| {
+        let end = frame * STFT_HOP;
+        let mut data: Vec<_> = (0..len).map(|i| {
+            let sample = if end + i >= len { x[end + i - len] } else { 0. };
+            Complex { re: sample * window[i], im: 0. }
+        })
+        .collect();
+        fft.process(&mut data);
+        let mut half: Vec<_> = data[..STFT_LEN].iter().flat_map(|x| [x.re, x.im]).collect();
+        half[1] = data[STFT_LEN].re;
+        half
+    })
+    .collect()
+}
+
+// one-sided density at a sample rate of 1, segments start every L - overlap samples
+fn welch_reference(planner: &mut FftPlanner<f64>, window: &[f64], overlap: usize, x: &[f64]) -> Vec<f64> {
+    let len = window.len();
+    let fft = planner.plan_fft_forward(len);
+    let mut power = vec![0.; len / 2 + 1];
+    let mut segments = 0;
+    for start in (0..=x.len() - len).step_by(len - overlap) {
+        let mut data: Vec<_> = (0..len).map(|i| Complex { re: x[start + i] * window[i], im: 0. }).collect();
+        fft.process(&mut data);
+        for (p, x) in power.iter_mut().zip(data.iter()) {
+            *p += x.norm_sqr();
+        }
+        segments += 1;
+    }
+    let energy: f64 = window.iter().map(|w| w * w).sum();
+    let last = power.len() - 1;
+    power.iter().enumerate().map(|(k, p)| {
+        let scale = if k == 0 || k == last { 1. } else { 2. };
+        scale * p / (segments as f64 * energy)
+    })
+    .collect()
+}
+
+// 4 term Blackman-Harris
+fn window_reference(len: usize) -> Vec<f64> {
+    let coeffs = [0.35875, -0.48829, 0.14128, -0.01168];
+    (0..len).map(|n| {
+        coeffs.iter().enumerate().map(|(j, a)| a * (2. * std::f64::consts::PI * (j * n) as f64 / len as f64).cos()).sum()
+    })
+    .collect()
+}
+
 macro_rules! mktest {
     ($($namespace:ident)*; $($size:literal)*) => {
         $( mod $namespace {
@@ -63,6 +433,16 @@ macro_rules! mktest {
                 }
             }
             
+            fn test_pairs_dyn<const N: usize>(data: &mut [Complex<f64>; N]) {
+                let mut data_re = data.map(|x| <_ as Convert>::from_f64(x.re));
+                let mut data_im = data.map(|x| <_ as Convert>::from_f64(x.im));
+                let range = nanofft::$namespace::fft_arrays_dyn(&mut data_re, &mut data_im);
+                for (dst, src) in data.iter_mut().zip(data_re.iter().zip(data_im.iter())) {
+                    dst.re = src.0.into_f64(range);
+                    dst.im = src.1.into_f64(range);
+                }
+            }
+            
             fn test_arrays<const N: usize>(data: &mut [Complex<f64>; N]) {
                 let mut data_t = data.map(|x| (<_ as Convert>::from_f64(x.re), <_ as Convert>::from_f64(x.im)));
                 let range = nanofft::$namespace::fft_pairs(&mut data_t);
@@ -95,6 +475,7 @@ macro_rules! mktest {
             
                 let results = [
                     test_pairs::<N>,
+                    test_pairs_dyn::<N>,
                     test_arrays::<N>,
                     test_arrays_dyn,
                 ]
@@ -117,6 +498,129 @@ macro_rules! mktest {
                 }
                 results[0]
             }
+
+            // The error of the kernels is taken relative to the energy of the
+            // whole spectrum, since a tone leaves most of the bins empty.
+            pub fn test_kernel(planner: &mut FftPlanner<f64>, kernel: super::Kernel, len: usize, full_scale: bool) -> f64 {
+                // every channel of the batched transform is a transform of its own
+                let points = match kernel {
+                    super::Kernel::Batch => len / super::CHANNELS,
+                    _ => len,
+                };
+                let data = super::kernel_input(len, points, full_scale);
+                let mut baseline = data.clone();
+                planner.plan_fft_forward(points).process(&mut baseline);
+
+                let mut data_t: Vec<_> = data.iter().map(|x| (<_ as Convert>::from_f64(x.re), <_ as Convert>::from_f64(x.im))).collect();
+                let range = match kernel {
+                    super::Kernel::Radix2 => nanofft::$namespace::fft_pairs_dyn(&mut data_t),
+                    super::Kernel::Radix4 => nanofft::$namespace::fft_pairs_radix4_dyn(&mut data_t),
+                    super::Kernel::SplitRadix => nanofft::$namespace::fft_pairs_split_radix_dyn(&mut data_t),
+                    super::Kernel::MixedRadix => nanofft::$namespace::fft_pairs_mixed_radix_dyn(&mut data_t),
+                    super::Kernel::Bluestein => {
+                        let zero = <_ as Convert>::from_f64(0.);
+                        let mut scratch = vec![(zero, zero); nanofft::fft_any_scratch_len(len)];
+                        nanofft::$namespace::fft_any_dyn(&mut data_t, &mut scratch)
+                    }
+                    super::Kernel::Batch => super::Batch::fft_batch(&mut data_t, super::CHANNELS),
+                };
+                let (mut error, mut energy) = (0_f64, 0_f64);
+                for (c1, c2) in data_t.iter().zip(baseline.iter()) {
+                    error += (c1.0.into_f64(range) - c2.re).powi(2) + (c1.1.into_f64(range) - c2.im).powi(2);
+                    energy += c2.re.powi(2) + c2.im.powi(2);
+                }
+                (error / energy).sqrt()
+            }
+
+            // Transforms real samples in [-1, 1), the error is taken relative to
+            // the energy of the reference output.
+            pub fn test_transform(planner: &mut FftPlanner<f64>, transform: super::Transform, len: usize) -> f64 {
+                let input = super::real_input(len);
+                let mut x: Vec<$namespace> = input.iter().map(|&x| <_ as Convert>::from_f64(x)).collect();
+                // the exact values of the converted samples
+                let samples: Vec<f64> = x.iter().map(|x| x.into_f64(<$namespace as Convert>::sample_range())).collect();
+                // real samples stored as pairs, as the real transforms take them
+                let mut pairs: Vec<_> = x.chunks(2).map(|x| (x[0], x[1])).collect();
+                let reals = |pairs: &[($namespace, $namespace)], range| -> Vec<f64> {
+                    pairs.iter().flat_map(|x| [x.0.into_f64(range), x.1.into_f64(range)]).collect()
+                };
+                let window = |len: usize| -> Vec<$namespace> { super::hann(len).iter().map(|&w| <_ as Convert>::from_f64(w)).collect() };
+                let exact = |window: &[$namespace]| -> Vec<f64> { window.iter().map(|w| w.into_f64(<$namespace as Convert>::sample_range())).collect() };
+
+                let (output, reference) = match transform {
+                    super::Transform::Dct2 => {
+                        let range = nanofft::$namespace::dct2_dyn(&mut pairs);
+                        (reals(&pairs, range), super::dct2_reference(&samples))
+                    }
+                    super::Transform::Mdct => {
+                        let range = nanofft::$namespace::mdct_dyn(&mut pairs);
+                        (reals(&pairs[..len / 4], range), super::mdct_reference(&samples))
+                    }
+                    super::Transform::Hartley => {
+                        let range = nanofft::$namespace::fht_dyn(&mut x);
+                        (x.iter().map(|x| x.into_f64(range)).collect(), super::hartley_reference(&samples))
+                    }
+                    super::Transform::Walsh => {
+                        let range = nanofft::$namespace::wht_dyn(&mut x, nanofft::WhtOrder::Natural);
+                        (x.iter().map(|x| x.into_f64(range)).collect(), super::walsh_reference(&samples))
+                    }
+                    super::Transform::Goertzel => {
+                        let mut power = [x[0]; super::GOERTZEL_BINS.len()];
+                        let range = nanofft::$namespace::goertzel_bins_dyn(&x, &super::GOERTZEL_BINS, &mut power);
+                        (power.iter().map(|x| x.into_f64(range)).collect(), super::goertzel_reference(&samples))
+                    }
+                    super::Transform::SlidingDft => {
+                        let damping = <_ as Convert>::from_f64(super::SLIDING_DAMPING);
+                        let mut sliding = nanofft::$namespace::SlidingDft::<{ super::SLIDING_LEN }, 4>::new(super::SLIDING_BINS, damping);
+                        let mut range = <$namespace as Convert>::sample_range();
+                        for &x in x.iter() {
+                            range = sliding.update(x);
+                        }
+                        let damping = damping.into_f64(<$namespace as Convert>::sample_range());
+                        (reals(sliding.bins(), range), super::sliding_reference(&samples, damping))
+                    }
+                    super::Transform::OverlapAdd => {
+                        let kernel = &x[..super::CONVOLUTION_TAIL + 1];
+                        let output = super::Streaming::overlap_add(kernel, &x);
+                        (output, super::convolution_reference(&samples[..kernel.len()], &samples))
+                    }
+                    super::Transform::CrossCorrelation => {
+                        let (a, b) = x.split_at(len / 2);
+                        let zero = <_ as Convert>::from_f64(0.);
+                        let mut data = vec![(zero, zero); len];
+                        let range = nanofft::$namespace::cross_correlation_dyn(a, b, &mut data);
+                        let (a, b) = samples.split_at(len / 2);
+                        (reals(&data[..len / 2], range), super::correlation_reference(a, b))
+                    }
+                    super::Transform::Autocorrelation => {
+                        let zero = <_ as Convert>::from_f64(0.);
+                        pairs.resize(len, (zero, zero));
+                        let range = nanofft::$namespace::autocorrelate_dyn(&mut pairs, nanofft::Estimator::Biased);
+                        (reals(&pairs[..len / 2], range), super::autocorrelation_reference(&samples))
+                    }
+                    super::Transform::Stft => {
+                        let window = window(2 * super::STFT_LEN);
+                        let output = super::Streaming::stft(&window, &x);
+                        (output, super::stft_reference(planner, &exact(&window), &samples))
+                    }
+                    super::Transform::Welch => {
+                        let window = window(super::WELCH_LEN);
+                        let output = super::Streaming::welch(&window, super::WELCH_LEN / 2, &x);
+                        (output, super::welch_reference(planner, &exact(&window), super::WELCH_LEN / 2, &samples))
+                    }
+                    super::Transform::Window => {
+                        nanofft::$namespace::window_dyn(nanofft::Window::BlackmanHarris, &mut x);
+                        (exact(&x), super::window_reference(len))
+                    }
+                };
+                assert_eq!(output.len(), reference.len());
+                let (mut error, mut energy) = (0_f64, 0_f64);
+                for (y, r) in output.iter().zip(reference.iter()) {
+                    error += (y - r).powi(2);
+                    energy += r.powi(2);
+                }
+                (error / energy).sqrt()
+            }
         } )* 
 
         fn run_tests(repeats: usize) {
@@ -138,6 +642,42 @@ macro_rules! mktest {
             )*
             
         }
+
+        fn run_kernel_tests(repeats: usize) {
+            let mut planner = FftPlanner::new();
+
+            for (name, kernel, len) in KERNELS {
+                for full_scale in [false, true] {
+                    print!("|{:<12}|{:>6}|{:<10}|", name, len, if full_scale { "full scale" } else { "random" });
+                    $({
+                        // the full scale input is the same every time
+                        let repeats = if full_scale { 1 } else { repeats };
+                        let mut e = 0.;
+                        for _ in 0..repeats {
+                            e += $namespace::test_kernel(&mut planner, kernel, len, full_scale);
+                        }
+                        print!("{:9.3e}|", e / (repeats as f64));
+                    })*
+                    println!();
+                }
+            }
+        }
+
+        fn run_transform_tests(repeats: usize) {
+            let mut planner = FftPlanner::new();
+
+            for (name, transform, len) in TRANSFORMS {
+                print!("|{:<12}|{:>6}|", name, len);
+                $({
+                    let mut e = 0.;
+                    for _ in 0..repeats {
+                        e += $namespace::test_transform(&mut planner, transform, len);
+                    }
+                    print!("{:9.3e}|", e / (repeats as f64));
+                })*
+                println!();
+            }
+        }
     };
 
 }
@@ -152,4 +692,20 @@ fn main() {
     run_tests(1);
     #[cfg(not(debug_assertions))]
     run_tests(1024);
+
+    println!();
+    println!("|kernel      |points|input     |   f32   |   f64   |   i16   |   i32   |");
+    println!("|:-----------|-----:|:---------|:-------:|:-------:|:-------:|:-------:|");
+    #[cfg(debug_assertions)]
+    run_kernel_tests(1);
+    #[cfg(not(debug_assertions))]
+    run_kernel_tests(64);
+
+    println!();
+    println!("|transform   |points|   f32   |   f64   |   i16   |   i32   |");
+    println!("|:-----------|-----:|:-------:|:-------:|:-------:|:-------:|");
+    #[cfg(debug_assertions)]
+    run_transform_tests(1);
+    #[cfg(not(debug_assertions))]
+    run_transform_tests(4);
 }
\ No newline at end of file